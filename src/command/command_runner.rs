@@ -1,16 +1,19 @@
 use crate::command::ffmpeg_command::FfmpegCommand;
 use crate::error::ProcessFailure;
-use log::debug;
+use log::{debug, warn};
 use std::collections::HashSet;
-use std::process::Command;
+use std::process::{Command, Output};
 use std::time::Instant;
 
 pub fn run_command(command: &FfmpegCommand) -> Result<(), ProcessFailure> {
+    let temp_files = command.temp_files();
     let command = command.as_cmd_string();
     println!("Running command: {:?}", command);
 
     let start = Instant::now();
-    let status_code = execute_and_wait(command)?;
+    let status_code = execute_and_wait(command);
+    remove_temp_files(temp_files);
+    let status_code = status_code?;
     if status_code != 0 {
         return Err(ProcessFailure::CommandExecution(status_code));
     };
@@ -19,6 +22,15 @@ pub fn run_command(command: &FfmpegCommand) -> Result<(), ProcessFailure> {
     Ok(())
 }
 
+fn remove_temp_files(files: &[String]) {
+    for file in files {
+        match std::fs::remove_file(file) {
+            Ok(_) => debug!("Removed temp file: {}", file),
+            Err(_) => warn!("Could not remove temp file: {}", file),
+        }
+    }
+}
+
 #[inline]
 fn get_system_specific_program_and_arg() -> (&'static str, &'static str) {
     if cfg!(target_os = "windows") {
@@ -69,16 +81,26 @@ pub fn get_supported_formats() -> HashSet<String> {
 }
 
 pub fn execute_cmd_get_lines(cmd: &str) -> Vec<String> {
+    let output = execute_cmd_get_output(cmd);
+    let std_out = String::from_utf8_lossy(&output.stdout);
+    let lines = std_out.lines().collect::<Vec<&str>>();
+    lines.into_iter().map(|line| line.to_owned()).collect()
+}
+
+/// ffmpeg filters like silencedetect report their findings on stderr.
+pub fn execute_cmd_get_stderr_lines(cmd: &str) -> Vec<String> {
+    let output = execute_cmd_get_output(cmd);
+    let std_err = String::from_utf8_lossy(&output.stderr);
+    std_err.lines().map(|line| line.to_owned()).collect()
+}
+
+fn execute_cmd_get_output(cmd: &str) -> Output {
     let program_arg = get_system_specific_program_and_arg();
-    let output = Command::new(program_arg.0)
+    Command::new(program_arg.0)
         .arg(program_arg.1)
         .arg(cmd)
         .output()
-        .expect("Failed to execute command. Is ffmpeg installed?");
-
-    let std_out = String::from_utf8_lossy(&output.stdout);
-    let lines = std_out.lines().collect::<Vec<&str>>();
-    lines.into_iter().map(|line| line.to_owned()).collect()
+        .expect("Failed to execute command. Is ffmpeg installed?")
 }
 
 pub fn get_ffmpeg_version() -> String {
@@ -1,4 +1,23 @@
+use crate::media::animation::{AnimationFormat, AnimationOptions};
+use crate::media::audio_merge::{AudioMergeMode, AudioMergeOptions};
+use crate::media::audio_settings::{AudioQuality, Resampler, SampleFormat};
+use crate::media::channels;
+use crate::media::channels::{ChannelOperation, ChannelOptions};
+use crate::media::chapters::ChapterOperation;
 use crate::media::codecs::{AudioCodec, CodecAsString, VideoCodec};
+use crate::media::cut_list::{CutListOptions, CutMode};
+use crate::media::detection::KeepRanges;
+use crate::media::metadata::MetadataEdit;
+use crate::media::orientation::DisplayMatrix;
+use crate::media::playback::{LoopOptions, ReverseOptions};
+use crate::media::scaling::Scaling;
+use crate::media::segment::SegmentOptions;
+use crate::media::smart_cut::SmartCutOptions;
+use crate::media::speed::SpeedChange;
+use crate::media::stream_mapping::StreamMapping;
+use crate::media::subtitles;
+use crate::media::subtitles::SubtitleOperation;
+use crate::media::thumbnail::ThumbnailOptions;
 use crate::string_utils;
 
 #[derive(Debug, Clone, Default)]
@@ -8,6 +27,23 @@ pub enum CommandType {
     ConvertFormat,
     YoutubeOptimized,
     MultiTask,
+    Animation(AnimationOptions),
+    Thumbnail(ThumbnailOptions),
+    Subtitles(SubtitleOperation),
+    Metadata(MetadataEdit),
+    Chapters(ChapterOperation),
+    Segment(SegmentOptions),
+    CutList(CutListOptions),
+    SmartCut(SmartCutOptions),
+    AudioMerge(AudioMergeOptions),
+    Channels(ChannelOptions),
+    KeepRanges(KeepRanges),
+    Orientation(DisplayMatrix),
+    Speed(SpeedChange),
+    Reverse(ReverseOptions),
+    Loop(LoopOptions),
+    /// Re-encodes the video through the video filter chain.
+    Filter,
 }
 
 #[derive(Default, Debug, derive_builder::Builder)]
@@ -20,16 +56,39 @@ pub struct FfmpegCommand {
     video_codec: VideoCodec,
     scale: Option<i16>,
     audio_bitrate: Option<i16>,
+    sample_rate: Option<u32>,
+    audio_channels: Option<u8>,
+    channel_layout: Option<String>,
+    sample_format: Option<SampleFormat>,
+    resampler: Option<Resampler>,
+    audio_quality: Option<AudioQuality>,
     preset: Option<String>,
     crf: Option<i16>,
+    stream_mapping: Option<StreamMapping>,
+    video_filters: Vec<String>,
+    scaling: Option<Scaling>,
+    /// Filters after scaling, e.g. watermarks, so their sizes and margins are kept.
+    overlays: Vec<String>,
+    temp_files: Vec<String>,
 }
 
 pub fn builder() -> FfmpegCommandBuilder {
     FfmpegCommandBuilder::default()
         .scale(None)
         .audio_bitrate(None)
+        .sample_rate(None)
+        .audio_channels(None)
+        .channel_layout(None)
+        .sample_format(None)
+        .resampler(None)
+        .audio_quality(None)
         .preset(None)
         .crf(None)
+        .stream_mapping(None)
+        .video_filters(Vec::new())
+        .scaling(None)
+        .overlays(Vec::new())
+        .temp_files(Vec::new())
         .to_owned()
 }
 
@@ -38,23 +97,156 @@ impl FfmpegCommand {
         let (input, output) =
             string_utils::add_quotes_if_whitespace_present(&self.input_file, &self.output_file);
 
+        match &self.command_type {
+            CommandType::Animation(options) => {
+                return animation_cmd_string(&input, &output, options);
+            }
+            CommandType::Thumbnail(options) => {
+                return format!(
+                    "ffmpeg{} -i {}{}{} {}",
+                    options.seek_args(),
+                    input,
+                    options.args(),
+                    options.format.quality_args(),
+                    output
+                );
+            }
+            CommandType::Subtitles(operation) => {
+                return self.subtitles_cmd_string(&input, &output, operation);
+            }
+            CommandType::Metadata(edit) => {
+                return format!(
+                    "ffmpeg -i {} -map 0 -c copy{} {}",
+                    input,
+                    edit.as_args(),
+                    output
+                );
+            }
+            CommandType::Chapters(operation) => {
+                return chapters_cmd_string(&input, &output, operation);
+            }
+            CommandType::Segment(options) => {
+                let codecs = match options {
+                    SegmentOptions::At(_) => {
+                        self.codecs_args() + &self.video_filter_args(Vec::new())
+                    }
+                    SegmentOptions::Every(_) => String::new(),
+                };
+                return format!(
+                    "ffmpeg -i {}{}{} {}",
+                    input,
+                    codecs,
+                    options.as_args(),
+                    output
+                );
+            }
+            CommandType::CutList(options) => {
+                return self.cut_list_cmd_string(&output, options);
+            }
+            CommandType::SmartCut(options) => {
+                return smart_cut_cmd_string(&input, &output, options);
+            }
+            CommandType::AudioMerge(options) => {
+                return self.audio_merge_cmd_string(&input, &output, options);
+            }
+            CommandType::Channels(options) => {
+                return self.channels_cmd_string(&input, &output, options);
+            }
+            CommandType::Orientation(matrix) => {
+                return format!(
+                    "ffmpeg{} -i {} -map 0 -c copy {}",
+                    matrix.as_args(),
+                    input,
+                    output
+                );
+            }
+            CommandType::Reverse(options) => {
+                return self.reverse_cmd_string(&input, &output, options);
+            }
+            CommandType::Loop(options) => {
+                let duration = options
+                    .duration
+                    .map(|d| format!(" -t {}", d))
+                    .unwrap_or_default();
+                return match &options.list_file {
+                    Some(list_file) => format!(
+                        "ffmpeg -f concat -safe 0 -i {} -map 0 -c copy{} {}",
+                        string_utils::wrap_with_quotes_if_whitespace(list_file),
+                        duration,
+                        output
+                    ),
+                    None => format!(
+                        "ffmpeg -stream_loop {} -i {} -map 0{}{}{} {}",
+                        options.count - 1,
+                        input,
+                        self.codecs_args(),
+                        self.video_filter_args(Vec::new()),
+                        duration,
+                        output
+                    ),
+                };
+            }
+            CommandType::KeepRanges(removal) => {
+                return format!(
+                    r#"ffmpeg -i {} -filter_complex "{}"{}{} {}"#,
+                    input,
+                    removal.filter(&self.video_filters),
+                    removal.maps(),
+                    self.codecs_args(),
+                    output
+                );
+            }
+            _ => {}
+        }
+
         let mut cmd = String::from("ffmpeg -i ");
         cmd.push_str(&input);
 
+        if let Some(mapping) = &self.stream_mapping {
+            cmd.push_str(&mapping.as_args());
+        }
         cmd.push_str(&self.codecs_args());
+        cmd.push_str(&self.audio_args());
 
-        match self.command_type {
-            CommandType::ConvertFormat => { /* skip */ }
+        match &self.command_type {
+            CommandType::ConvertFormat => cmd.push_str(&self.video_filter_args(Vec::new())),
             CommandType::Compress => {
-                cmd.push_str(r#" -vf "scale=1280:-2" -preset veryslow -crf 24"#);
+                let scale = match &self.scaling {
+                    Some(scaling) => scaling.filters(),
+                    None => vec!["scale=1280:-2".to_owned()],
+                };
+                cmd.push_str(&self.video_filter_args(scale));
+                cmd.push_str(" -preset veryslow -crf 24");
             }
             CommandType::YoutubeOptimized => {
+                cmd.push_str(&self.video_filter_args(self.scaling_filters()));
                 cmd.push_str(" -crf 23 -preset medium -b:a 320k -qscale 0");
             }
-            CommandType::MultiTask => {
-                if let Some(v) = &self.scale {
-                    cmd.push_str(format!(r#" -vf "scale={}-2""#, v).as_str());
-                }
+            CommandType::Speed(change) => {
+                cmd.push_str(&self.video_filter_args(change.video_filters()));
+                cmd.push_str(&change.args());
+                cmd.push_str(" -preset medium -crf 18");
+            }
+            CommandType::Animation(_)
+            | CommandType::Thumbnail(_)
+            | CommandType::Subtitles(_)
+            | CommandType::Metadata(_)
+            | CommandType::Chapters(_)
+            | CommandType::Segment(_)
+            | CommandType::CutList(_)
+            | CommandType::SmartCut(_)
+            | CommandType::AudioMerge(_)
+            | CommandType::Channels(_)
+            | CommandType::KeepRanges(_)
+            | CommandType::Orientation(_)
+            | CommandType::Reverse(_)
+            | CommandType::Loop(_) => { /* handled above */ }
+            CommandType::MultiTask | CommandType::Filter => {
+                let scale = match self.scale {
+                    Some(v) => vec![format!("scale={}-2", v)],
+                    None => self.scaling_filters(),
+                };
+                cmd.push_str(&self.video_filter_args(scale));
                 if let Some(v) = &self.audio_bitrate {
                     cmd.push_str(format!(" -b:a {}k", v).as_str());
                 }
@@ -75,9 +267,17 @@ impl FfmpegCommand {
         let mut cmd = String::from("");
         match self.command_type {
             CommandType::ConvertFormat => { /* skip additional params */ }
+            CommandType::Animation(_) => { /* skip additional params */ }
+            CommandType::Thumbnail(_) => { /* skip additional params */ }
             _ => {
                 let video_codec = self.video_codec.as_str().to_lowercase();
                 let audio_codec = self.audio_codec.as_str().to_lowercase();
+                let audio_codec = match &self.sample_format {
+                    Some(format) if audio_codec.starts_with("pcm_") => {
+                        format.pcm_codec().to_owned()
+                    }
+                    _ => audio_codec,
+                };
                 cmd.push_str(" -c:v ");
                 cmd.push_str(&video_codec);
                 cmd.push_str(" -c:a ");
@@ -86,11 +286,464 @@ impl FfmpegCommand {
         };
         cmd
     }
+
+    fn scaling_filters(&self) -> Vec<String> {
+        self.scaling
+            .as_ref()
+            .map(|scaling| scaling.filters())
+            .unwrap_or_default()
+    }
+
+    /// The `-vf` argument for the filter chain, with `last` (e.g. the final scale) and the
+    /// overlays appended.
+    fn video_filter_args(&self, last: Vec<String>) -> String {
+        let filters = self
+            .video_filters
+            .iter()
+            .cloned()
+            .chain(last)
+            .chain(self.overlays.iter().cloned())
+            .collect::<Vec<_>>();
+        match filters.is_empty() {
+            true => String::new(),
+            false => format!(r#" -vf "{}""#, filters.join(",")),
+        }
+    }
+
+    fn audio_args(&self) -> String {
+        let mut args = String::new();
+        if let Some(resampler) = &self.resampler {
+            args.push_str(format!(r#" -af "{}""#, resampler.filter()).as_str());
+        }
+        if let Some(v) = &self.sample_rate {
+            args.push_str(format!(" -ar {}", v).as_str());
+        }
+        if let Some(v) = &self.channel_layout {
+            args.push_str(format!(" -ch_layout {}", v).as_str());
+        }
+        if let Some(v) = &self.audio_channels {
+            args.push_str(format!(" -ac {}", v).as_str());
+        }
+        let audio_codec = self.audio_codec.as_str().to_lowercase();
+        match &self.sample_format {
+            Some(format) if audio_codec == "flac" && format.supported_by(&audio_codec) => {
+                args.push_str(format.as_args())
+            }
+            _ => {}
+        }
+        match &self.audio_quality {
+            Some(quality) => args.push_str(&quality.as_args()),
+            // libopus defaults to VBR, a plain bitrate should stay constant
+            None if audio_codec == "libopus" && self.audio_bitrate.is_some() => {
+                args.push_str(" -vbr off")
+            }
+            None => {}
+        }
+        args
+    }
+
+    fn subtitles_cmd_string(
+        &self,
+        input: &str,
+        output: &str,
+        operation: &SubtitleOperation,
+    ) -> String {
+        let mut cmd = format!("ffmpeg -i {}", input);
+        match operation {
+            SubtitleOperation::BurnIn {
+                subtitle_file,
+                style,
+            } => {
+                let filter = subtitles::burn_in_filter(subtitle_file, style);
+                cmd.push_str(&self.video_filter_args(vec![filter]));
+                cmd.push_str(&self.codecs_args());
+            }
+            SubtitleOperation::Mux {
+                tracks,
+                existing_subtitles,
+            } => {
+                for track in tracks {
+                    let file = string_utils::wrap_with_quotes_if_whitespace(&track.file);
+                    cmd.push_str(format!(" -i {}", file).as_str());
+                }
+                cmd.push_str(" -map 0");
+                for i in 1..=tracks.len() {
+                    cmd.push_str(format!(" -map {}", i).as_str());
+                }
+                cmd.push_str(" -c copy");
+                for (i, track) in tracks.iter().enumerate() {
+                    let n = existing_subtitles + i;
+                    let codec =
+                        subtitles::mux_codec(&self.output_file, &track.file).unwrap_or("copy");
+                    cmd.push_str(format!(" -c:s:{} {}", n, codec).as_str());
+                    if let Some(language) = &track.language {
+                        cmd.push_str(
+                            format!(" -metadata:s:s:{} language={}", n, language).as_str(),
+                        );
+                    }
+                }
+            }
+            SubtitleOperation::Extract { streams } => {
+                for stream in streams {
+                    let file = string_utils::wrap_with_quotes_if_whitespace(&stream.output_file);
+                    cmd.push_str(
+                        format!(
+                            " -map 0:{} -c:s {} {}",
+                            stream.stream_index,
+                            subtitles::extract_codec(&stream.output_file),
+                            file
+                        )
+                        .as_str(),
+                    );
+                }
+                return cmd;
+            }
+        }
+        cmd.push(' ');
+        cmd.push_str(output);
+        cmd
+    }
+
+    /// Reverses every chunk on its own, then joins them last chunk first.
+    fn reverse_cmd_string(&self, input: &str, output: &str, options: &ReverseOptions) -> String {
+        let quote = string_utils::wrap_with_quotes_if_whitespace;
+        let video_codec = self.video_codec.as_str().to_lowercase();
+        let mut commands = options
+            .chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                // the same pass writes the chunk's reversed audio next to its video
+                let audio = match options.audio_parts.get(i) {
+                    Some(audio_part) => format!(
+                        " -map 0:a:0 -af areverse -c:a pcm_s16le -y {}",
+                        quote(audio_part)
+                    ),
+                    None => String::new(),
+                };
+                format!(
+                    "ffmpeg -ss {} -i {} -t {} -map 0:v:0{} -c:v {} -y {}{}",
+                    chunk.start,
+                    input,
+                    chunk.duration(),
+                    self.video_filter_args(vec!["reverse".to_owned()]),
+                    video_codec,
+                    quote(&options.parts[i]),
+                    audio
+                )
+            })
+            .collect::<Vec<_>>();
+        let audio = match &options.audio_list_file {
+            Some(audio_list_file) => format!(
+                " -f concat -safe 0 -i {} -map 0:v -map 1:a -c:v copy -c:a {}",
+                quote(audio_list_file),
+                self.audio_codec.as_str().to_lowercase()
+            ),
+            None => " -map 0:v -c copy".to_owned(),
+        };
+        commands.push(format!(
+            "ffmpeg -f concat -safe 0 -i {}{} {}",
+            quote(&options.list_file),
+            audio,
+            output
+        ));
+        commands.join(" && ")
+    }
+
+    fn cut_list_cmd_string(&self, output: &str, options: &CutListOptions) -> String {
+        let quote = string_utils::wrap_with_quotes_if_whitespace;
+        match &options.mode {
+            CutMode::Lossless { parts, list_file } => {
+                let mut commands = options
+                    .clips
+                    .iter()
+                    .zip(parts)
+                    .map(|(clip, part)| {
+                        format!(
+                            "ffmpeg -ss {} -i {} -t {} -map 0 -c copy -avoid_negative_ts make_zero -y {}",
+                            clip.start,
+                            quote(&clip.source),
+                            clip.end - clip.start,
+                            quote(part)
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                commands.push(format!(
+                    "ffmpeg -f concat -safe 0 -i {} -map 0 -c copy {}",
+                    quote(list_file),
+                    output
+                ));
+                commands.join(" && ")
+            }
+            CutMode::FrameAccurate { has_audio } => {
+                // e.g. the deinterlacer, run on every piece before it is trimmed
+                let prefix = self
+                    .video_filters
+                    .iter()
+                    .map(|f| format!("{},", f))
+                    .collect::<String>();
+                let mut sources: Vec<&str> = Vec::new();
+                let mut filter = String::new();
+                for (i, clip) in options.clips.iter().enumerate() {
+                    let input = match sources.iter().position(|s| *s == clip.source) {
+                        Some(input) => input,
+                        None => {
+                            sources.push(&clip.source);
+                            sources.len() - 1
+                        }
+                    };
+                    filter.push_str(
+                        format!(
+                            "[{input}:v]{prefix}trim=start={s}:end={e},setpts=PTS-STARTPTS[v{i}];",
+                            s = clip.start,
+                            e = clip.end
+                        )
+                        .as_str(),
+                    );
+                    if *has_audio {
+                        filter.push_str(
+                            format!(
+                                "[{input}:a]atrim=start={s}:end={e},asetpts=PTS-STARTPTS[a{i}];",
+                                s = clip.start,
+                                e = clip.end
+                            )
+                            .as_str(),
+                        );
+                    }
+                }
+                for i in 0..options.clips.len() {
+                    filter.push_str(format!("[v{i}]").as_str());
+                    if *has_audio {
+                        filter.push_str(format!("[a{i}]").as_str());
+                    }
+                }
+                let (audio, outputs, maps) = match has_audio {
+                    true => (1, "[v][a]", r#" -map "[v]" -map "[a]""#),
+                    false => (0, "[v]", r#" -map "[v]""#),
+                };
+                filter.push_str(
+                    format!(
+                        "concat=n={}:v=1:a={}{}",
+                        options.clips.len(),
+                        audio,
+                        outputs
+                    )
+                    .as_str(),
+                );
+
+                let mut cmd = String::from("ffmpeg");
+                for source in sources {
+                    cmd.push_str(format!(" -i {}", quote(source)).as_str());
+                }
+                cmd.push_str(format!(r#" -filter_complex "{}"{}"#, filter, maps).as_str());
+                cmd.push_str(&self.codecs_args());
+                cmd.push(' ');
+                cmd.push_str(output);
+                cmd
+            }
+        }
+    }
+
+    fn audio_merge_cmd_string(
+        &self,
+        input: &str,
+        output: &str,
+        options: &AudioMergeOptions,
+    ) -> String {
+        let video_codec = self.video_codec.as_str().to_lowercase();
+        let audio_codec = self.audio_codec.as_str().to_lowercase();
+        let mut cmd = format!("ffmpeg -i {}{}", input, options.input_args());
+        match &options.mode {
+            AudioMergeMode::Replace => {
+                cmd.push_str(" -map 0:v");
+                for i in 1..=options.inputs.len() {
+                    cmd.push_str(format!(" -map {}:a", i).as_str());
+                }
+                cmd.push_str(format!(" -c:v {} -c:a {}", video_codec, audio_codec).as_str());
+                cmd.push_str(&options.track_metadata_args(0));
+                cmd.push_str(" -shortest");
+            }
+            AudioMergeMode::AddTracks { existing_tracks } => {
+                cmd.push_str(" -map 0");
+                for i in 1..=options.inputs.len() {
+                    cmd.push_str(format!(" -map {}:a", i).as_str());
+                }
+                cmd.push_str(" -c copy");
+                for i in 0..options.inputs.len() {
+                    let n = existing_tracks + i;
+                    cmd.push_str(format!(" -c:a:{} {}", n, audio_codec).as_str());
+                }
+                cmd.push_str(&options.track_metadata_args(*existing_tracks));
+            }
+            AudioMergeMode::Mix { original_volume } => {
+                let filter = options.mix_filter(*original_volume);
+                cmd.push_str(&mixed_audio_args(&filter, &video_codec, &audio_codec));
+            }
+            AudioMergeMode::Duck(ducking) => {
+                let filter = options.ducking_filter(ducking);
+                cmd.push_str(&mixed_audio_args(&filter, &video_codec, &audio_codec));
+            }
+        }
+        cmd.push(' ');
+        cmd.push_str(output);
+        cmd
+    }
+
+    fn channels_cmd_string(&self, input: &str, output: &str, options: &ChannelOptions) -> String {
+        let audio_codec = self.audio_codec.as_str().to_lowercase();
+        match &options.operation {
+            ChannelOperation::SplitMono { outputs } => {
+                let mut cmd = format!(
+                    r#"ffmpeg -i {} -filter_complex "{}""#,
+                    input,
+                    options.filter()
+                );
+                let channels = channels::layout_channels(&options.layout).unwrap_or_default();
+                for (channel, file) in channels.iter().zip(outputs.iter()) {
+                    cmd.push_str(
+                        format!(
+                            r#" -map "[{}]" -c:a {} {}"#,
+                            channel,
+                            audio_codec,
+                            string_utils::wrap_with_quotes_if_whitespace(file)
+                        )
+                        .as_str(),
+                    );
+                }
+                cmd
+            }
+            _ => format!(
+                r#"ffmpeg -i {} -map 0:v? -map 0:a:{} -c:v copy -af "{}" -c:a {} {}"#,
+                input,
+                options.stream,
+                options.filter(),
+                audio_codec,
+                output
+            ),
+        }
+    }
+
+    pub fn output_file(&self) -> &str {
+        &self.output_file
+    }
+
+    pub fn temp_files(&self) -> &[String] {
+        &self.temp_files
+    }
+}
+
+fn animation_cmd_string(input: &str, output: &str, options: &AnimationOptions) -> String {
+    let trim = options.trim_args();
+    let filter = options.scale_filter();
+    match options.format {
+        AnimationFormat::Gif => {
+            let palette = string_utils::wrap_with_quotes_if_whitespace(&options.palette_file);
+            format!(
+                r#"ffmpeg{trim} -i {input} -vf "{filter},palettegen" -y {palette} && ffmpeg{trim} -i {input} -i {palette} -lavfi "{filter} [x]; [x][1:v] paletteuse=dither={}" {output}"#,
+                options.dither.as_str()
+            )
+        }
+        AnimationFormat::WebP => {
+            format!(r#"ffmpeg{trim} -i {input} -vf "{filter}" -c:v libwebp -loop 0 -an {output}"#)
+        }
+    }
+}
+
+fn chapters_cmd_string(input: &str, output: &str, operation: &ChapterOperation) -> String {
+    match operation {
+        ChapterOperation::Write { metadata_file } => {
+            let metadata_file = string_utils::wrap_with_quotes_if_whitespace(metadata_file);
+            format!(
+                "ffmpeg -i {} -i {} -map 0 -map_metadata 0 -map_chapters 1 -c copy {}",
+                input, metadata_file, output
+            )
+        }
+        ChapterOperation::Split { outputs } => outputs
+            .iter()
+            .map(|file| {
+                format!(
+                    "ffmpeg -ss {} -i {} -t {} -map 0 -map_chapters -1 -c copy {}",
+                    file.chapter.start,
+                    input,
+                    file.chapter.end - file.chapter.start,
+                    string_utils::wrap_with_quotes_if_whitespace(&file.output_file)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" && "),
+    }
+}
+
+fn mixed_audio_args(filter: &str, video_codec: &str, audio_codec: &str) -> String {
+    format!(
+        r#" -filter_complex "{}" -map 0:v -map "[aout]" -c:v {} -c:a {}"#,
+        filter, video_codec, audio_codec
+    )
+}
+
+fn smart_cut_cmd_string(input: &str, output: &str, options: &SmartCutOptions) -> String {
+    let mut commands = options
+        .parts
+        .iter()
+        .map(|part| {
+            let codecs = match part.copy {
+                true => options.params.copy_args(),
+                false => options.params.as_args(),
+            };
+            format!(
+                "ffmpeg -ss {} -i {} -t {} -map 0:v:0 -map 0:a:0?{} -avoid_negative_ts make_zero -y {}",
+                part.start,
+                input,
+                part.end - part.start,
+                codecs,
+                string_utils::wrap_with_quotes_if_whitespace(&part.file)
+            )
+        })
+        .collect::<Vec<_>>();
+    commands.push(format!(
+        "ffmpeg -f concat -safe 0 -i {} -map 0 -c copy{} {}",
+        string_utils::wrap_with_quotes_if_whitespace(&options.list_file),
+        options.params.remux_args(),
+        output
+    ));
+    commands.join(" && ")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::media::animation::DitherMode;
+    use crate::media::audio_merge::{AudioInput, DuckingOptions};
+    use crate::media::audio_settings::ResampleEngine;
+    use crate::media::channels::MonoSource;
+    use crate::media::chapters::{Chapter, ChapterFile};
+    use crate::media::cut_list::Clip;
+    use crate::media::detection::Interval;
+    use crate::media::scaling::{Padding, ScalePolicy, SourceSize};
+    use crate::media::smart_cut::{CutPart, EncodeParams};
+    use crate::media::stream_mapping::StreamSelection;
+    use crate::media::subtitles::{ExtractedSubtitle, SubtitleStyle, SubtitleTrack};
+    use crate::media::thumbnail::{ImageFormat, ThumbnailMode};
+    use crate::media::watermark::{Position, Watermark};
+
+    fn cmd_string(
+        command_type: CommandType,
+        input: &str,
+        output: &str,
+        audio_codec: AudioCodec,
+        video_codec: VideoCodec,
+    ) -> String {
+        builder()
+            .command_type(command_type)
+            .input_file(input)
+            .output_file(output)
+            .audio_codec(audio_codec)
+            .video_codec(video_codec)
+            .build()
+            .unwrap()
+            .as_cmd_string()
+    }
+
     #[test]
     fn should_build_convert_format_command() {
         let cmd = builder()
@@ -109,6 +762,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_build_convert_format_command_with_deinterlacing() {
+        let cmd = builder()
+            .command_type(CommandType::ConvertFormat)
+            .input_file("/aaa/bbb/input_video.mpg")
+            .output_file("/ccc/ddd/output_video.mp4")
+            .audio_codec(AudioCodec::default())
+            .video_codec(VideoCodec::default())
+            .video_filters(vec!["bwdif=mode=send_frame:parity=tff:deint=all".to_owned()])
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            r#"ffmpeg -i /aaa/bbb/input_video.mpg -vf "bwdif=mode=send_frame:parity=tff:deint=all" /ccc/ddd/output_video.mp4"#,
+            cmd,
+        );
+    }
+
     #[test]
     fn should_build_compress_command() {
         let cmd = builder()
@@ -127,6 +799,213 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_build_compress_command_with_video_filters() {
+        let cmd = builder()
+            .command_type(CommandType::Compress)
+            .input_file("/aaa/bbb/input_video.avi")
+            .output_file("/ccc/ddd/output_video.mp4")
+            .audio_codec(AudioCodec::Aac)
+            .video_codec(VideoCodec::Libx264)
+            .video_filters(vec!["crop=1920:800:0:140".to_owned()])
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            r#"ffmpeg -i /aaa/bbb/input_video.avi -c:v libx264 -c:a aac -vf "crop=1920:800:0:140,scale=1280:-2" -preset veryslow -crf 24 /ccc/ddd/output_video.mp4"#,
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_compress_command_with_scaling_policy() {
+        let scaling = Scaling {
+            policy: ScalePolicy {
+                padding: Some(Padding::Blur),
+                ..Default::default()
+            },
+            source: SourceSize {
+                width: 1080,
+                height: 1920,
+                sample_aspect_ratio: 1.0,
+                rotation: 0,
+            },
+        };
+        let cmd = builder()
+            .command_type(CommandType::Compress)
+            .input_file("/aaa/vertical.mp4")
+            .output_file("/aaa/vertical_compressed.mp4")
+            .audio_codec(AudioCodec::Aac)
+            .video_codec(VideoCodec::Libx264)
+            .scaling(scaling)
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            r#"ffmpeg -i /aaa/vertical.mp4 -c:v libx264 -c:a aac -vf "split[bg][fg];[bg]scale=720:1280:force_original_aspect_ratio=increase:flags=lanczos,crop=720:1280,boxblur=20:5[bg];[fg]scale=720:1280:flags=lanczos[fg];[bg][fg]overlay=(W-w)/2:(H-h)/2,setsar=1" -preset veryslow -crf 24 /aaa/vertical_compressed.mp4"#,
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_compress_command_with_watermark() {
+        let watermark = Watermark::Image {
+            path: "/logos/logo.png".to_owned(),
+            position: Position::TopRight,
+            margin: 20,
+            scale: 0.1,
+            opacity: 1.0,
+        };
+        let cmd = builder()
+            .command_type(CommandType::Compress)
+            .input_file("/aaa/input_video.mp4")
+            .output_file("/aaa/input_video_compressed.mp4")
+            .audio_codec(AudioCodec::Aac)
+            .video_codec(VideoCodec::Libx264)
+            .overlays(vec![watermark.filter()])
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mp4 -c:v libx264 -c:a aac -vf "scale=1280:-2,null[main];movie=/logos/logo.png,format=rgba[logo];[logo][main]scale2ref=w=main_w*0.1:h=ow/a[logo][main];[main][logo]overlay=x=W-w-20:y=20" -preset veryslow -crf 24 /aaa/input_video_compressed.mp4"#,
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_filter_command() {
+        let cmd = builder()
+            .command_type(CommandType::Filter)
+            .input_file("/aaa/input_video.mp4")
+            .output_file("/bbb/output_video.mp4")
+            .audio_codec(AudioCodec::Custom("copy".to_owned()))
+            .video_codec(VideoCodec::Libx264)
+            .video_filters(vec!["crop=1920:800:0:140".to_owned()])
+            .preset("medium".to_owned())
+            .crf(18)
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mp4 -c:v libx264 -c:a copy -vf "crop=1920:800:0:140" -preset medium -crf 18 /bbb/output_video.mp4"#,
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_speed_commands() {
+        let speed = |change: SpeedChange| {
+            cmd_string(
+                CommandType::Speed(change),
+                "/aaa/input_video.mp4",
+                "/bbb/output_video.mp4",
+                AudioCodec::Aac,
+                VideoCodec::Libx264,
+            )
+        };
+
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mp4 -c:v libx264 -c:a aac -vf "setpts=PTS/3" -af "atempo=2,atempo=1.5" -preset medium -crf 18 /bbb/output_video.mp4"#,
+            speed(SpeedChange::Factor {
+                factor: 3.0,
+                keep_audio: true
+            }),
+        );
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mp4 -c:v libx264 -c:a aac -vf "fps=1/5,setpts=N/30/TB" -r 30 -an -preset medium -crf 18 /bbb/output_video.mp4"#,
+            speed(SpeedChange::Timelapse {
+                interval: 5.0,
+                frame_rate: 30
+            }),
+        );
+    }
+
+    #[test]
+    fn should_build_reverse_command_in_chunks() {
+        let cmd = builder()
+            .command_type(CommandType::Reverse(ReverseOptions {
+                chunks: vec![
+                    Interval {
+                        start: 0.0,
+                        end: 10.0,
+                    },
+                    Interval {
+                        start: 10.0,
+                        end: 15.0,
+                    },
+                ],
+                parts: vec!["/tmp/r1.mkv".to_owned(), "/tmp/r2.mkv".to_owned()],
+                list_file: "/tmp/concat.txt".to_owned(),
+                audio_parts: vec!["/tmp/r1.wav".to_owned(), "/tmp/r2.wav".to_owned()],
+                audio_list_file: Some("/tmp/audio_concat.txt".to_owned()),
+            }))
+            .input_file("/aaa/input_video.mp4")
+            .output_file("/bbb/output_video.mp4")
+            .audio_codec(AudioCodec::Aac)
+            .video_codec(VideoCodec::Libx264)
+            .video_filters(vec!["yadif".to_owned()])
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            r#"ffmpeg -ss 0 -i /aaa/input_video.mp4 -t 10 -map 0:v:0 -vf "yadif,reverse" -c:v libx264 -y /tmp/r1.mkv -map 0:a:0 -af areverse -c:a pcm_s16le -y /tmp/r1.wav && ffmpeg -ss 10 -i /aaa/input_video.mp4 -t 5 -map 0:v:0 -vf "yadif,reverse" -c:v libx264 -y /tmp/r2.mkv -map 0:a:0 -af areverse -c:a pcm_s16le -y /tmp/r2.wav && ffmpeg -f concat -safe 0 -i /tmp/concat.txt -f concat -safe 0 -i /tmp/audio_concat.txt -map 0:v -map 1:a -c:v copy -c:a aac /bbb/output_video.mp4"#,
+            cmd,
+        );
+
+        let silent = ReverseOptions {
+            chunks: vec![Interval {
+                start: 0.0,
+                end: 4.0,
+            }],
+            parts: vec!["/tmp/r1.mkv".to_owned()],
+            list_file: "/tmp/concat.txt".to_owned(),
+            audio_parts: Vec::new(),
+            audio_list_file: None,
+        };
+        assert_eq!(
+            r#"ffmpeg -ss 0 -i /aaa/input_video.mp4 -t 4 -map 0:v:0 -vf "reverse" -c:v libx264 -y /tmp/r1.mkv && ffmpeg -f concat -safe 0 -i /tmp/concat.txt -map 0:v -c copy /bbb/output_video.mp4"#,
+            cmd_string(
+                CommandType::Reverse(silent),
+                "/aaa/input_video.mp4",
+                "/bbb/output_video.mp4",
+                AudioCodec::Aac,
+                VideoCodec::Libx264,
+            ),
+        );
+    }
+
+    #[test]
+    fn should_build_loop_command() {
+        let looped = |list_file: Option<&str>| {
+            let options = LoopOptions {
+                count: 360,
+                list_file: list_file.map(|f| f.to_owned()),
+                duration: Some(3600.0),
+            };
+            cmd_string(
+                CommandType::Loop(options),
+                "/aaa/input_video.mp4",
+                "/bbb/output_video.mp4",
+                AudioCodec::Aac,
+                VideoCodec::Libx264,
+            )
+        };
+
+        assert_eq!(
+            "ffmpeg -f concat -safe 0 -i /tmp/concat.txt -map 0 -c copy -t 3600 /bbb/output_video.mp4",
+            looped(Some("/tmp/concat.txt")),
+        );
+        assert_eq!(
+            "ffmpeg -stream_loop 359 -i /aaa/input_video.mp4 -map 0 -c:v libx264 -c:a aac -t 3600 /bbb/output_video.mp4",
+            looped(None),
+        );
+    }
+
     #[test]
     fn should_build_youtube_command() {
         let cmd = builder()
@@ -167,6 +1046,87 @@ mod tests {
         )
     }
 
+    #[test]
+    fn should_build_multi_task_command_with_audio_settings() {
+        let cmd = builder()
+            .command_type(CommandType::MultiTask)
+            .input_file("/aaa/input_audio.flac")
+            .output_file("/bbb/output_audio.wav")
+            .audio_codec(AudioCodec::Custom("pcm_s16le".to_owned()))
+            .video_codec(VideoCodec::Custom("copy".to_owned()))
+            .sample_rate(44100u32)
+            .audio_channels(2u8)
+            .sample_format(SampleFormat::S24)
+            .resampler(Resampler {
+                engine: ResampleEngine::Soxr,
+                dither_method: Some("triangular".to_owned()),
+            })
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_audio.flac -c:v copy -c:a pcm_s24le -af "aresample=resampler=soxr:precision=28:dither_method=triangular" -ar 44100 -ac 2 /bbb/output_audio.wav"#,
+            cmd,
+        );
+        let cmd = |audio_codec: &str| {
+            builder()
+                .command_type(CommandType::MultiTask)
+                .input_file("/aaa/input_video.mov")
+                .output_file("/bbb/output_video.mkv")
+                .audio_codec(AudioCodec::Custom(audio_codec.to_owned()))
+                .video_codec(VideoCodec::Custom("copy".to_owned()))
+                .channel_layout("5.1".to_owned())
+                .audio_channels(6u8)
+                .sample_format(SampleFormat::S24)
+                .build()
+                .unwrap()
+                .as_cmd_string()
+        };
+        assert_eq!(
+            "ffmpeg -i /aaa/input_video.mov -c:v copy -c:a flac -ch_layout 5.1 -ac 6 -sample_fmt s32 -bits_per_raw_sample 24 /bbb/output_video.mkv",
+            cmd("flac"),
+        );
+        // lossy encoders ignore the sample format
+        assert_eq!(
+            "ffmpeg -i /aaa/input_video.mov -c:v copy -c:a aac -ch_layout 5.1 -ac 6 /bbb/output_video.mkv",
+            cmd("aac"),
+        );
+    }
+
+    #[test]
+    fn should_build_vbr_audio_commands() {
+        let cmd = builder()
+            .command_type(CommandType::MultiTask)
+            .input_file("/aaa/input_video.mp4")
+            .output_file("/bbb/output_audio.mp3")
+            .audio_codec(AudioCodec::Custom("libmp3lame".to_owned()))
+            .video_codec(VideoCodec::Custom("copy".to_owned()))
+            .audio_quality(AudioQuality::Vbr(2.0))
+            .build()
+            .unwrap()
+            .as_cmd_string();
+        assert_eq!(
+            "ffmpeg -i /aaa/input_video.mp4 -c:v copy -c:a libmp3lame -q:a 2 /bbb/output_audio.mp3",
+            cmd,
+        );
+
+        let cmd = builder()
+            .command_type(CommandType::MultiTask)
+            .input_file("/aaa/input_video.mp4")
+            .output_file("/bbb/output_audio.opus")
+            .audio_codec(AudioCodec::Custom("libopus".to_owned()))
+            .video_codec(VideoCodec::Custom("copy".to_owned()))
+            .audio_bitrate(96)
+            .build()
+            .unwrap()
+            .as_cmd_string();
+        assert_eq!(
+            "ffmpeg -i /aaa/input_video.mp4 -c:v copy -c:a libopus -vbr off -b:a 96k /bbb/output_audio.opus",
+            cmd,
+        );
+    }
+
     #[test]
     fn should_build_multi_task_command_and_skip_optional_arguments() {
         let cmd = builder()
@@ -184,4 +1144,695 @@ mod tests {
             cmd,
         );
     }
+
+    fn animation_options(format: AnimationFormat) -> AnimationOptions {
+        AnimationOptions {
+            format,
+            start: Some(5.0),
+            duration: Some(3.5),
+            fps: 12,
+            width: 480,
+            dither: DitherMode::Bayer,
+            palette_file: "/tmp/palette.png".to_owned(),
+        }
+    }
+
+    #[test]
+    fn should_build_gif_command() {
+        let cmd = builder()
+            .command_type(CommandType::Animation(animation_options(
+                AnimationFormat::Gif,
+            )))
+            .input_file("/aaa/input_video.mp4")
+            .output_file("/bbb/output.gif")
+            .audio_codec(AudioCodec::default())
+            .video_codec(VideoCodec::default())
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            r#"ffmpeg -ss 5 -t 3.5 -i /aaa/input_video.mp4 -vf "fps=12,scale=480:-1:flags=lanczos,palettegen" -y /tmp/palette.png && ffmpeg -ss 5 -t 3.5 -i /aaa/input_video.mp4 -i /tmp/palette.png -lavfi "fps=12,scale=480:-1:flags=lanczos [x]; [x][1:v] paletteuse=dither=bayer" /bbb/output.gif"#,
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_webp_animation_command() {
+        let cmd = builder()
+            .command_type(CommandType::Animation(animation_options(
+                AnimationFormat::WebP,
+            )))
+            .input_file("/aaa/input_video.mp4")
+            .output_file("/bbb/output.webp")
+            .audio_codec(AudioCodec::default())
+            .video_codec(VideoCodec::default())
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            r#"ffmpeg -ss 5 -t 3.5 -i /aaa/input_video.mp4 -vf "fps=12,scale=480:-1:flags=lanczos" -c:v libwebp -loop 0 -an /bbb/output.webp"#,
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_single_frame_command() {
+        let options = ThumbnailOptions {
+            mode: ThumbnailMode::Frame { timestamp: 62.5 },
+            format: ImageFormat::Jpeg,
+        };
+        let cmd = cmd_string(
+            CommandType::Thumbnail(options),
+            "/aaa/input_video.mp4",
+            "/bbb/frame.jpg",
+            AudioCodec::default(),
+            VideoCodec::default(),
+        );
+
+        assert_eq!(
+            "ffmpeg -ss 62.5 -i /aaa/input_video.mp4 -frames:v 1 -q:v 2 /bbb/frame.jpg",
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_thumbnail_series_command() {
+        let options = ThumbnailOptions {
+            mode: ThumbnailMode::Series {
+                count: 4,
+                duration: 100.0,
+            },
+            format: ImageFormat::Png,
+        };
+        let cmd = cmd_string(
+            CommandType::Thumbnail(options),
+            "/aaa/input_video.mp4",
+            "/bbb/thumb_%03d.png",
+            AudioCodec::default(),
+            VideoCodec::default(),
+        );
+
+        assert_eq!(
+            r#"ffmpeg -ss 12.500 -i /aaa/input_video.mp4 -vf "fps=4/100" -frames:v 4 /bbb/thumb_%03d.png"#,
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_contact_sheet_command() {
+        let options = ThumbnailOptions {
+            mode: ThumbnailMode::ContactSheet {
+                columns: 4,
+                rows: 3,
+                duration: 120.0,
+                width: 320,
+                labels: true,
+                header_file: Some("/tmp/header.txt".to_owned()),
+            },
+            format: ImageFormat::Png,
+        };
+        let cmd = cmd_string(
+            CommandType::Thumbnail(options),
+            "/aaa/input_video.mp4",
+            "/bbb/sheet.png",
+            AudioCodec::default(),
+            VideoCodec::default(),
+        );
+
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mp4 -vf "fps=12/120,scale=320:-1,drawtext=text='%{pts\:hms}':x=5:y=h-th-5:fontsize=16:fontcolor=white:box=1:boxcolor=black@0.6,tile=4x3:padding=4:margin=4,pad=iw:ih+40:0:40:color=black,drawtext=textfile=/tmp/header.txt:x=10:y=12:fontsize=16:fontcolor=white" -frames:v 1 /bbb/sheet.png"#,
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_burn_in_subtitles_command() {
+        let style = SubtitleStyle {
+            font_name: Some("Arial".to_owned()),
+            font_size: Some(24),
+            ..Default::default()
+        };
+        let operation = SubtitleOperation::BurnIn {
+            subtitle_file: "/aaa/subs.srt".to_owned(),
+            style,
+        };
+        let cmd = cmd_string(
+            CommandType::Subtitles(operation),
+            "/aaa/input_video.mp4",
+            "/bbb/output_video.mp4",
+            AudioCodec::Custom("copy".to_owned()),
+            VideoCodec::Libx264,
+        );
+
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mp4 -vf "subtitles=/aaa/subs.srt:force_style=FontName=Arial\\,FontSize=24" -c:v libx264 -c:a copy /bbb/output_video.mp4"#,
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_mux_subtitles_command() {
+        let tracks = vec![
+            SubtitleTrack {
+                file: "/aaa/eng.srt".to_owned(),
+                language: Some("eng".to_owned()),
+            },
+            SubtitleTrack {
+                file: "/aaa/pol.ass".to_owned(),
+                language: None,
+            },
+        ];
+        let operation = SubtitleOperation::Mux {
+            tracks,
+            existing_subtitles: 1,
+        };
+        let cmd = cmd_string(
+            CommandType::Subtitles(operation),
+            "/aaa/input_video.mp4",
+            "/bbb/output_video.mkv",
+            AudioCodec::Custom("copy".to_owned()),
+            VideoCodec::Libx264,
+        );
+
+        assert_eq!(
+            "ffmpeg -i /aaa/input_video.mp4 -i /aaa/eng.srt -i /aaa/pol.ass -map 0 -map 1 -map 2 -c copy -c:s:1 srt -metadata:s:s:1 language=eng -c:s:2 ass /bbb/output_video.mkv",
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_extract_subtitles_command() {
+        let streams = vec![
+            ExtractedSubtitle {
+                stream_index: 2,
+                output_file: "/bbb/video.eng.srt".to_owned(),
+            },
+            ExtractedSubtitle {
+                stream_index: 3,
+                output_file: "/bbb/video.pol.vtt".to_owned(),
+            },
+        ];
+        let cmd = cmd_string(
+            CommandType::Subtitles(SubtitleOperation::Extract { streams }),
+            "/aaa/input_video.mp4",
+            "/bbb/video.srt",
+            AudioCodec::Custom("copy".to_owned()),
+            VideoCodec::Libx264,
+        );
+
+        assert_eq!(
+            "ffmpeg -i /aaa/input_video.mp4 -map 0:2 -c:s srt /bbb/video.eng.srt -map 0:3 -c:s webvtt /bbb/video.pol.vtt",
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_command_with_language_stream_mapping() {
+        let mapping = StreamMapping {
+            selection: StreamSelection::Languages(vec!["eng".to_owned(), "pol".to_owned()]),
+            drop_subtitles: false,
+            drop_data: true,
+        };
+        let cmd = builder()
+            .command_type(CommandType::Compress)
+            .input_file("/aaa/input_video.mkv")
+            .output_file("/bbb/output_video.mkv")
+            .audio_codec(AudioCodec::Aac)
+            .video_codec(VideoCodec::Libx264)
+            .stream_mapping(mapping)
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mkv -map 0:v? -map 0:a:m:language:eng? -map 0:a:m:language:pol? -map 0:s:m:language:eng? -map 0:s:m:language:pol? -map -0:d -c:v libx264 -c:a aac -vf "scale=1280:-2" -preset veryslow -crf 24 /bbb/output_video.mkv"#,
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_command_keeping_all_streams_but_subtitles() {
+        let mapping = StreamMapping {
+            selection: StreamSelection::All,
+            drop_subtitles: true,
+            drop_data: false,
+        };
+        let cmd = builder()
+            .command_type(CommandType::ConvertFormat)
+            .input_file("/aaa/input_video.mkv")
+            .output_file("/bbb/output_video.mp4")
+            .audio_codec(AudioCodec::default())
+            .video_codec(VideoCodec::default())
+            .stream_mapping(mapping)
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            "ffmpeg -i /aaa/input_video.mkv -map 0 -map -0:s /bbb/output_video.mp4",
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_set_metadata_command() {
+        let edit = MetadataEdit::Set {
+            tags: vec![
+                ("title".to_owned(), "Weekly meeting".to_owned()),
+                ("artist".to_owned(), "Team".to_owned()),
+            ],
+            language: Some("pol".to_owned()),
+        };
+        let cmd = builder()
+            .command_type(CommandType::Metadata(edit))
+            .input_file("/aaa/input_video.mp4")
+            .output_file("/bbb/output_video.mp4")
+            .audio_codec(AudioCodec::default())
+            .video_codec(VideoCodec::default())
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mp4 -map 0 -c copy -metadata title="Weekly meeting" -metadata artist="Team" -metadata:s:a language=pol -metadata:s:s language=pol /bbb/output_video.mp4"#,
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_strip_metadata_command() {
+        let cmd = builder()
+            .command_type(CommandType::Metadata(MetadataEdit::StripAll))
+            .input_file("/aaa/input_video.mov")
+            .output_file("/bbb/output_video.mov")
+            .audio_codec(AudioCodec::default())
+            .video_codec(VideoCodec::default())
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            "ffmpeg -i /aaa/input_video.mov -map 0 -c copy -map -0:d -map_metadata -1 -map_metadata:s -1 -map_chapters -1 -fflags +bitexact /bbb/output_video.mov",
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_write_chapters_command() {
+        let operation = ChapterOperation::Write {
+            metadata_file: "/tmp/chapters.txt".to_owned(),
+        };
+        let cmd = builder()
+            .command_type(CommandType::Chapters(operation))
+            .input_file("/aaa/meeting.mp4")
+            .output_file("/bbb/meeting_chapters.mp4")
+            .audio_codec(AudioCodec::default())
+            .video_codec(VideoCodec::default())
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            "ffmpeg -i /aaa/meeting.mp4 -i /tmp/chapters.txt -map 0 -map_metadata 0 -map_chapters 1 -c copy /bbb/meeting_chapters.mp4",
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_split_by_chapters_command() {
+        let chapter = |start: f64, end: f64, title: &str| Chapter {
+            start,
+            end,
+            title: title.to_owned(),
+        };
+        let outputs = vec![
+            ChapterFile {
+                chapter: chapter(0.0, 300.0, "Intro"),
+                output_file: "/aaa/meeting_01_Intro.mp4".to_owned(),
+            },
+            ChapterFile {
+                chapter: chapter(300.0, 900.5, "Q and A"),
+                output_file: "/aaa/meeting_02_Q and A.mp4".to_owned(),
+            },
+        ];
+        let cmd = builder()
+            .command_type(CommandType::Chapters(ChapterOperation::Split { outputs }))
+            .input_file("/aaa/meeting.mp4")
+            .output_file("/aaa/meeting_01_Intro.mp4")
+            .audio_codec(AudioCodec::default())
+            .video_codec(VideoCodec::default())
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            r#"ffmpeg -ss 0 -i /aaa/meeting.mp4 -t 300 -map 0 -map_chapters -1 -c copy /aaa/meeting_01_Intro.mp4 && ffmpeg -ss 300 -i /aaa/meeting.mp4 -t 600.5 -map 0 -map_chapters -1 -c copy "/aaa/meeting_02_Q and A.mp4""#,
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_segment_command() {
+        let cmd = builder()
+            .command_type(CommandType::Segment(SegmentOptions::Every(600.0)))
+            .input_file("/aaa/lecture.mp4")
+            .output_file("/aaa/lecture_part%03d.mp4")
+            .audio_codec(AudioCodec::default())
+            .video_codec(VideoCodec::default())
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            "ffmpeg -i /aaa/lecture.mp4 -map 0 -c copy -f segment -segment_time 600 -segment_start_number 1 -reset_timestamps 1 /aaa/lecture_part%03d.mp4",
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_split_at_scenes_command() {
+        let cmd = builder()
+            .command_type(CommandType::Segment(SegmentOptions::At(vec![4.12, 30.0])))
+            .input_file("/aaa/footage.mp4")
+            .output_file("/aaa/footage_scene%03d.mp4")
+            .audio_codec(AudioCodec::Aac)
+            .video_codec(VideoCodec::Libx264)
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            "ffmpeg -i /aaa/footage.mp4 -c:v libx264 -c:a aac -map 0:v:0 -map 0:a? -force_key_frames 4.12,30 -f segment -segment_times 4.12,30 -segment_start_number 1 -reset_timestamps 1 /aaa/footage_scene%03d.mp4",
+            cmd,
+        );
+    }
+
+    fn clips() -> Vec<Clip> {
+        vec![
+            Clip {
+                source: "/aaa/a.mp4".to_owned(),
+                start: 10.0,
+                end: 20.0,
+            },
+            Clip {
+                source: "/aaa/b.mp4".to_owned(),
+                start: 5.5,
+                end: 7.0,
+            },
+        ]
+    }
+
+    #[test]
+    fn should_build_lossless_cut_list_command() {
+        let mode = CutMode::Lossless {
+            parts: vec!["/tmp/part1.mp4".to_owned(), "/tmp/part2.mp4".to_owned()],
+            list_file: "/tmp/list.txt".to_owned(),
+        };
+        let cmd = cmd_string(
+            CommandType::CutList(CutListOptions {
+                clips: clips(),
+                mode,
+            }),
+            "/aaa/a.mp4",
+            "/bbb/assembled.mp4",
+            AudioCodec::Aac,
+            VideoCodec::Libx264,
+        );
+
+        assert_eq!(
+            "ffmpeg -ss 10 -i /aaa/a.mp4 -t 10 -map 0 -c copy -avoid_negative_ts make_zero -y /tmp/part1.mp4 && ffmpeg -ss 5.5 -i /aaa/b.mp4 -t 1.5 -map 0 -c copy -avoid_negative_ts make_zero -y /tmp/part2.mp4 && ffmpeg -f concat -safe 0 -i /tmp/list.txt -map 0 -c copy /bbb/assembled.mp4",
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_frame_accurate_cut_list_command() {
+        let cut = |has_audio: bool| {
+            let options = CutListOptions {
+                clips: clips(),
+                mode: CutMode::FrameAccurate { has_audio },
+            };
+            cmd_string(
+                CommandType::CutList(options),
+                "/aaa/a.mp4",
+                "/bbb/assembled.mp4",
+                AudioCodec::Aac,
+                VideoCodec::Libx264,
+            )
+        };
+
+        assert_eq!(
+            r#"ffmpeg -i /aaa/a.mp4 -i /aaa/b.mp4 -filter_complex "[0:v]trim=start=10:end=20,setpts=PTS-STARTPTS[v0];[0:a]atrim=start=10:end=20,asetpts=PTS-STARTPTS[a0];[1:v]trim=start=5.5:end=7,setpts=PTS-STARTPTS[v1];[1:a]atrim=start=5.5:end=7,asetpts=PTS-STARTPTS[a1];[v0][a0][v1][a1]concat=n=2:v=1:a=1[v][a]" -map "[v]" -map "[a]" -c:v libx264 -c:a aac /bbb/assembled.mp4"#,
+            cut(true),
+        );
+        // a source without audio drops the audio of the whole result
+        assert_eq!(
+            r#"ffmpeg -i /aaa/a.mp4 -i /aaa/b.mp4 -filter_complex "[0:v]trim=start=10:end=20,setpts=PTS-STARTPTS[v0];[1:v]trim=start=5.5:end=7,setpts=PTS-STARTPTS[v1];[v0][v1]concat=n=2:v=1:a=0[v]" -map "[v]" -c:v libx264 -c:a aac /bbb/assembled.mp4"#,
+            cut(false),
+        );
+    }
+
+    #[test]
+    fn should_build_smart_cut_command() {
+        let part = |start: f64, end: f64, copy: bool, file: &str| CutPart {
+            start,
+            end,
+            copy,
+            file: file.to_owned(),
+        };
+        let options = SmartCutOptions {
+            parts: vec![
+                part(1.5, 2.0, false, "/tmp/p1.ts"),
+                part(2.0, 6.0, true, "/tmp/p2.ts"),
+            ],
+            params: EncodeParams {
+                video_codec: "h264".to_owned(),
+                video_encoder: "libx264".to_owned(),
+                profile: Some("high".to_owned()),
+                level: Some("4.0".to_owned()),
+                refs: None,
+                bit_rate: None,
+                pix_fmt: Some("yuv420p".to_owned()),
+                timescale: Some("12800".to_owned()),
+                audio_encoder: Some("aac".to_owned()),
+                sample_rate: Some(48000),
+                channels: Some(2),
+            },
+            list_file: "/tmp/list.txt".to_owned(),
+        };
+        let cmd = builder()
+            .command_type(CommandType::SmartCut(options))
+            .input_file("/aaa/input_video.mp4")
+            .output_file("/bbb/output_video.mp4")
+            .audio_codec(AudioCodec::default())
+            .video_codec(VideoCodec::default())
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            "ffmpeg -ss 1.5 -i /aaa/input_video.mp4 -t 0.5 -map 0:v:0 -map 0:a:0? -c:v libx264 -profile:v high -crf 18 -level 4.0 -x264-params repeat-headers=1 -pix_fmt yuv420p -c:a aac -ar 48000 -ac 2 -avoid_negative_ts make_zero -y /tmp/p1.ts \
+            && ffmpeg -ss 2 -i /aaa/input_video.mp4 -t 4 -map 0:v:0 -map 0:a:0? -c copy -bsf:v h264_mp4toannexb -avoid_negative_ts make_zero -y /tmp/p2.ts \
+            && ffmpeg -f concat -safe 0 -i /tmp/list.txt -map 0 -c copy -video_track_timescale 12800 /bbb/output_video.mp4",
+            cmd,
+        );
+    }
+
+    fn audio_merge(mode: AudioMergeMode, offset_ms: i64) -> CommandType {
+        let inputs = vec![AudioInput {
+            file: "/aaa/music.wav".to_owned(),
+            volume: 0.3,
+            language: Some("eng".to_owned()),
+            title: Some("Music bed".to_owned()),
+        }];
+        CommandType::AudioMerge(AudioMergeOptions {
+            mode,
+            inputs,
+            offset_ms,
+        })
+    }
+
+    #[test]
+    fn should_build_replace_audio_command() {
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mp4 -itsoffset -0.25 -i /aaa/music.wav -map 0:v -map 1:a -c:v copy -c:a aac -metadata:s:a:0 language=eng -metadata:s:a:0 title="Music bed" -shortest /bbb/output_video.mp4"#,
+            cmd_string(
+                audio_merge(AudioMergeMode::Replace, -250),
+                "/aaa/input_video.mp4",
+                "/bbb/output_video.mp4",
+                AudioCodec::Aac,
+                VideoCodec::Custom("copy".to_owned()),
+            ),
+        );
+    }
+
+    #[test]
+    fn should_build_add_audio_track_command() {
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mp4 -i /aaa/music.wav -map 0 -map 1:a -c copy -c:a:1 aac -metadata:s:a:1 language=eng -metadata:s:a:1 title="Music bed" /bbb/output_video.mp4"#,
+            cmd_string(
+                audio_merge(AudioMergeMode::AddTracks { existing_tracks: 1 }, 0),
+                "/aaa/input_video.mp4",
+                "/bbb/output_video.mp4",
+                AudioCodec::Aac,
+                VideoCodec::Custom("copy".to_owned()),
+            ),
+        );
+    }
+
+    #[test]
+    fn should_build_mix_audio_command() {
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mp4 -i /aaa/music.wav -filter_complex "[0:a]volume=1[a0];[1:a]adelay=1500:all=1,volume=0.3[a1];[a0][a1]amix=inputs=2:duration=first:dropout_transition=0:normalize=0[aout]" -map 0:v -map "[aout]" -c:v copy -c:a aac /bbb/output_video.mp4"#,
+            cmd_string(
+                audio_merge(
+                    AudioMergeMode::Mix {
+                        original_volume: 1.0
+                    },
+                    1500
+                ),
+                "/aaa/input_video.mp4",
+                "/bbb/output_video.mp4",
+                AudioCodec::Aac,
+                VideoCodec::Custom("copy".to_owned()),
+            ),
+        );
+    }
+
+    #[test]
+    fn should_build_ducking_command() {
+        let ducking = DuckingOptions {
+            duration: 60.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mp4 -stream_loop -1 -i /aaa/music.wav -filter_complex "[1:a]volume=0.3,atrim=0:60,afade=t=out:st=57:d=3[music];[0:a]asplit=2[voice][sc];[music][sc]sidechaincompress=threshold=0.05:ratio=8:attack=20:release=400[ducked];[voice][ducked]amix=inputs=2:duration=first:dropout_transition=0:normalize=0[aout]" -map 0:v -map "[aout]" -c:v copy -c:a aac /bbb/output_video.mp4"#,
+            cmd_string(
+                audio_merge(AudioMergeMode::Duck(ducking.clone()), 0),
+                "/aaa/input_video.mp4",
+                "/bbb/output_video.mp4",
+                AudioCodec::Aac,
+                VideoCodec::Custom("copy".to_owned()),
+            ),
+        );
+        // a negative offset cuts the start of the music instead of delaying it
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mp4 -stream_loop -1 -i /aaa/music.wav -filter_complex "[1:a]atrim=start=0.5,asetpts=PTS-STARTPTS,volume=0.3,atrim=0:60,afade=t=out:st=57:d=3[music];[0:a]asplit=2[voice][sc];[music][sc]sidechaincompress=threshold=0.05:ratio=8:attack=20:release=400[ducked];[voice][ducked]amix=inputs=2:duration=first:dropout_transition=0:normalize=0[aout]" -map 0:v -map "[aout]" -c:v copy -c:a aac /bbb/output_video.mp4"#,
+            cmd_string(
+                audio_merge(AudioMergeMode::Duck(ducking), -500),
+                "/aaa/input_video.mp4",
+                "/bbb/output_video.mp4",
+                AudioCodec::Aac,
+                VideoCodec::Custom("copy".to_owned()),
+            ),
+        );
+    }
+
+    fn channels(operation: ChannelOperation) -> CommandType {
+        CommandType::Channels(ChannelOptions {
+            stream: 1,
+            layout: "stereo".to_owned(),
+            operation,
+        })
+    }
+
+    #[test]
+    fn should_build_channel_commands() {
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mkv -map 0:v? -map 0:a:1 -c:v copy -af "pan=mono|c0=0.5*c0+0.5*c1" -c:a aac /bbb/output_video.mkv"#,
+            cmd_string(
+                channels(ChannelOperation::ToMono(MonoSource::Both)),
+                "/aaa/input_video.mkv",
+                "/bbb/output_video.mkv",
+                AudioCodec::Aac,
+                VideoCodec::Custom("copy".to_owned()),
+            ),
+        );
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mkv -map 0:v? -map 0:a:1 -c:v copy -af "channelmap=map=FR|FL:channel_layout=stereo" -c:a aac /bbb/output_video.mkv"#,
+            cmd_string(
+                channels(ChannelOperation::Remap {
+                    order: vec!["FR".to_owned(), "FL".to_owned()]
+                }),
+                "/aaa/input_video.mkv",
+                "/bbb/output_video.mkv",
+                AudioCodec::Aac,
+                VideoCodec::Custom("copy".to_owned()),
+            ),
+        );
+    }
+
+    #[test]
+    fn should_build_split_mono_command() {
+        let outputs = vec!["/bbb/out_FL.wav".to_owned(), "/bbb/out FR.wav".to_owned()];
+        assert_eq!(
+            r#"ffmpeg -i /aaa/input_video.mkv -filter_complex "[0:a:1]channelsplit=channel_layout=stereo[FL][FR]" -map "[FL]" -c:a pcm_s24le /bbb/out_FL.wav -map "[FR]" -c:a pcm_s24le "/bbb/out FR.wav""#,
+            cmd_string(
+                channels(ChannelOperation::SplitMono { outputs }),
+                "/aaa/input_video.mkv",
+                "/bbb/output_video.mkv",
+                AudioCodec::Custom("pcm_s24le".to_owned()),
+                VideoCodec::Custom("copy".to_owned()),
+            ),
+        );
+    }
+
+    #[test]
+    fn should_build_silence_removal_command() {
+        let removal = KeepRanges::new(
+            vec![
+                Interval {
+                    start: 0.0,
+                    end: 4.5,
+                },
+                Interval {
+                    start: 9.5,
+                    end: 20.0,
+                },
+            ],
+            true,
+            true,
+        )
+        .unwrap();
+        let cmd = builder()
+            .command_type(CommandType::KeepRanges(removal))
+            .input_file("/aaa/lecture.mp4")
+            .output_file("/bbb/lecture_nosilence.mp4")
+            .audio_codec(AudioCodec::Aac)
+            .video_codec(VideoCodec::Libx264)
+            .video_filters(vec!["yadif".to_owned()])
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            r#"ffmpeg -i /aaa/lecture.mp4 -filter_complex "[0:v]yadif,trim=start=0:end=4.5,setpts=PTS-STARTPTS[v0];[0:a]atrim=start=0:end=4.5,asetpts=PTS-STARTPTS[a0];[0:v]yadif,trim=start=9.5:end=20,setpts=PTS-STARTPTS[v1];[0:a]atrim=start=9.5:end=20,asetpts=PTS-STARTPTS[a1];[v0][a0][v1][a1]concat=n=2:v=1:a=1[v][a]" -map "[v]" -map "[a]" -c:v libx264 -c:a aac /bbb/lecture_nosilence.mp4"#,
+            cmd,
+        );
+    }
+
+    #[test]
+    fn should_build_lossless_rotation_command() {
+        let matrix = DisplayMatrix {
+            rotation: 90,
+            horizontal_flip: false,
+            vertical_flip: true,
+        };
+        let cmd = builder()
+            .command_type(CommandType::Orientation(matrix))
+            .input_file("/aaa/phone video.mp4")
+            .output_file("/aaa/phone video_rotated.mp4")
+            .audio_codec(AudioCodec::default())
+            .video_codec(VideoCodec::default())
+            .build()
+            .unwrap()
+            .as_cmd_string();
+
+        assert_eq!(
+            r#"ffmpeg -display_rotation:v:0 270 -display_vflip:v:0 -i "/aaa/phone video.mp4" -map 0 -c copy "/aaa/phone video_rotated.mp4""#,
+            cmd,
+        );
+    }
 }
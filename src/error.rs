@@ -16,6 +16,8 @@ pub enum TranscoderError {
     SameInputAndOutput,
     UnsupportedOperation,
     AbortTranscoding(String),
+    ProbeFailed(String),
+    InvalidList(String),
 }
 
 impl Display for TranscoderError {
@@ -31,6 +33,29 @@ impl Display for TranscoderError {
             }
             TranscoderError::AbortTranscoding(reason) => format!("Aborted. {}", reason).fmt(f),
             TranscoderError::UnsupportedOperation => f.write_str("Unsupported operation."),
+            TranscoderError::ProbeFailed(path) => format!("Failed to probe file: {}.", path).fmt(f),
+            TranscoderError::InvalidList(entry) => format!("Invalid list entry: {}", entry).fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SubtitleError {
+    UnsupportedFormat(String),
+    InvalidTimestamp(usize),
+    MissingEventFormat,
+}
+
+impl Display for SubtitleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubtitleError::UnsupportedFormat(path) => {
+                format!("Unsupported subtitle format: {}.", path).fmt(f)
+            }
+            SubtitleError::InvalidTimestamp(line) => {
+                format!("Invalid timestamp at line {}.", line).fmt(f)
+            }
+            SubtitleError::MissingEventFormat => f.write_str("Missing [Events] format line."),
         }
     }
 }
@@ -49,3 +74,4 @@ impl Display for ProcessFailure {
 
 impl Error for TranscoderError {}
 impl Error for ProcessFailure {}
+impl Error for SubtitleError {}
@@ -3,6 +3,7 @@ use crate::command::command_runner::get_ffmpeg_version;
 use crate::command::ffmpeg_command::FfmpegCommand;
 use crate::error::TranscoderError;
 use crate::media::transcoder;
+use crate::subtitle::subtitle_tool;
 use log::debug;
 use std::error::Error;
 
@@ -11,6 +12,7 @@ mod error;
 mod logger_config;
 mod media;
 mod string_utils;
+mod subtitle;
 mod user_input;
 
 fn print_menu() {
@@ -19,11 +21,36 @@ fn print_menu() {
     println!("2. Compress video.");
     println!("3. Complex command.");
     println!("4. Convert into Youtube optimized format.");
+    println!("5. Create animated GIF/WebP.");
+    println!("6. Extract thumbnails or contact sheet.");
+    println!("7. Subtitles (burn-in, add tracks, extract).");
+    println!("8. Fix subtitle file (shift, frame rate, format).");
+    println!("9. Edit or strip metadata.");
+    println!("10. Chapters (write, split by chapter).");
+    println!("11. Split into segments by duration or size.");
+    println!("12. Assemble clips from a cut list (csv/edl).");
+    println!("13. Smart frame-accurate cut.");
+    println!("14. Replace, add or mix audio.");
+    println!("15. Audio channels (downmix, split, remap).");
+    println!("16. Remove or shorten silence.");
+    println!("17. Detect scenes and black frames.");
+    println!("18. Remove black bars (auto-crop).");
+    println!("19. Rotate, flip or fix orientation.");
+    println!("20. Frame rate (variable to constant, change rate).");
+    println!("21. Speed (faster, slower, timelapse).");
+    println!("22. Reverse.");
+    println!("23. Loop.");
+    println!("24. Watermark (image or text).");
+    println!("25. Text overlays (timecode, file name, labels).");
     println!("0. Exit program.");
 }
 
 fn main() {
     logger_config::setup_logger();
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if !args.is_empty() {
+        run_subcommand(&args);
+    }
     println!("Welcome to ffmpeg-cli!");
     println!("Installed ffmpeg version is: {}", get_ffmpeg_version());
     loop {
@@ -41,6 +68,27 @@ fn handle_menu_option(option: i32) {
         2 => transcoder::compress(),
         3 => transcoder::multi_task(),
         4 => transcoder::youtube_optimized(),
+        5 => transcoder::animation(),
+        6 => transcoder::thumbnails(),
+        7 => transcoder::subtitles(),
+        8 => return print_error(subtitle_tool::run_interactive()),
+        9 => return print_error(transcoder::edit_metadata()),
+        10 => transcoder::chapters(),
+        11 => transcoder::split(),
+        12 => transcoder::cut_list(),
+        13 => transcoder::smart_cut(),
+        14 => transcoder::merge_audio(),
+        15 => transcoder::channels(),
+        16 => transcoder::remove_silence(),
+        17 => return print_error(transcoder::analyze_scenes()),
+        18 => transcoder::remove_black_bars(),
+        19 => transcoder::orientation(),
+        20 => transcoder::frame_rate(),
+        21 => transcoder::speed(),
+        22 => transcoder::reverse(),
+        23 => transcoder::loop_clip(),
+        24 => transcoder::watermark(),
+        25 => transcoder::text_overlays(),
         0 => std::process::exit(0),
         _ => Err(TranscoderError::UnsupportedOperation.into()),
     };
@@ -51,6 +99,26 @@ fn handle_menu_option(option: i32) {
     }
 }
 
+fn print_error(result: Result<(), Box<dyn Error>>) {
+    if let Err(error) = result {
+        eprintln!("{}", error);
+    }
+}
+
+fn run_subcommand(args: &[String]) {
+    let result = match args[0].as_str() {
+        "subtitles" => subtitle_tool::run_subcommand(&args[1..]),
+        _ => Err(TranscoderError::UnsupportedOperation.into()),
+    };
+    match result {
+        Ok(_) => std::process::exit(0),
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
 #[inline(always)]
 fn run_cmd(cmd: &FfmpegCommand) {
     match command_runner::run_command(cmd) {
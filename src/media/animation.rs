@@ -0,0 +1,67 @@
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum AnimationFormat {
+    #[default]
+    Gif,
+    WebP,
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum DitherMode {
+    Bayer,
+    FloydSteinberg,
+    Sierra2,
+    #[default]
+    Sierra2_4a,
+    None,
+}
+
+impl DitherMode {
+    pub fn as_str(&self) -> &str {
+        match self {
+            DitherMode::Bayer => "bayer",
+            DitherMode::FloydSteinberg => "floyd_steinberg",
+            DitherMode::Sierra2 => "sierra2",
+            DitherMode::Sierra2_4a => "sierra2_4a",
+            DitherMode::None => "none",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<DitherMode> {
+        match s.to_lowercase().as_str() {
+            "bayer" => Some(DitherMode::Bayer),
+            "floyd_steinberg" => Some(DitherMode::FloydSteinberg),
+            "sierra2" => Some(DitherMode::Sierra2),
+            "sierra2_4a" => Some(DitherMode::Sierra2_4a),
+            "none" => Some(DitherMode::None),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationOptions {
+    pub format: AnimationFormat,
+    pub start: Option<f64>,
+    pub duration: Option<f64>,
+    pub fps: u8,
+    pub width: u16,
+    pub dither: DitherMode,
+    pub palette_file: String,
+}
+
+impl AnimationOptions {
+    pub fn trim_args(&self) -> String {
+        let mut args = String::new();
+        if let Some(start) = self.start {
+            args.push_str(format!(" -ss {}", start).as_str());
+        }
+        if let Some(duration) = self.duration {
+            args.push_str(format!(" -t {}", duration).as_str());
+        }
+        args
+    }
+
+    pub fn scale_filter(&self) -> String {
+        format!("fps={},scale={}:-1:flags=lanczos", self.fps, self.width)
+    }
+}
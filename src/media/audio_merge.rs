@@ -0,0 +1,139 @@
+use crate::string_utils;
+
+#[derive(Debug, Clone)]
+pub struct AudioInput {
+    pub file: String,
+    pub volume: f64,
+    pub language: Option<String>,
+    pub title: Option<String>,
+}
+
+/// Sidechain compression of the music by the main audio. Defaults suit a voice-over.
+#[derive(Debug, Clone)]
+pub struct DuckingOptions {
+    pub threshold: f64,
+    pub ratio: f64,
+    pub attack_ms: f64,
+    pub release_ms: f64,
+    pub duration: f64,
+    pub fade_out: f64,
+}
+
+impl Default for DuckingOptions {
+    fn default() -> Self {
+        DuckingOptions {
+            threshold: 0.05,
+            ratio: 8.0,
+            attack_ms: 20.0,
+            release_ms: 400.0,
+            duration: 0.0,
+            fade_out: 3.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AudioMergeMode {
+    Replace,
+    AddTracks { existing_tracks: usize },
+    Mix { original_volume: f64 },
+    Duck(DuckingOptions),
+}
+
+#[derive(Debug, Clone)]
+pub struct AudioMergeOptions {
+    pub mode: AudioMergeMode,
+    pub inputs: Vec<AudioInput>,
+    pub offset_ms: i64,
+}
+
+impl AudioMergeOptions {
+    /// Extra `-i` arguments. Mapped tracks are delayed by the sync offset here, mixed ones in the
+    /// filter, since `amix` and `sidechaincompress` ignore the start time of their inputs.
+    pub fn input_args(&self) -> String {
+        let mut args = String::new();
+        let mixed = matches!(
+            self.mode,
+            AudioMergeMode::Mix { .. } | AudioMergeMode::Duck(_)
+        );
+        for input in self.inputs.iter() {
+            if let AudioMergeMode::Duck(_) = self.mode {
+                // the music is looped, atrim in the filter cuts it at the end of the video
+                args.push_str(" -stream_loop -1");
+            }
+            if self.offset_ms != 0 && !mixed {
+                args.push_str(format!(" -itsoffset {}", self.offset_ms as f64 / 1000.0).as_str());
+            }
+            let file = string_utils::wrap_with_quotes_if_whitespace(&input.file);
+            args.push_str(format!(" -i {}", file).as_str());
+        }
+        args
+    }
+
+    pub fn mix_filter(&self, original_volume: f64) -> String {
+        let mut filter = format!("[0:a]volume={}[a0];", original_volume);
+        for (i, input) in self.inputs.iter().enumerate() {
+            filter.push_str(
+                format!(
+                    "[{n}:a]{}volume={}[a{n}];",
+                    self.offset_filter(),
+                    input.volume,
+                    n = i + 1
+                )
+                .as_str(),
+            );
+        }
+        for i in 0..=self.inputs.len() {
+            filter.push_str(format!("[a{}]", i).as_str());
+        }
+        filter.push_str(
+            format!(
+                "amix=inputs={}:duration=first:dropout_transition=0:normalize=0[aout]",
+                self.inputs.len() + 1
+            )
+            .as_str(),
+        );
+        filter
+    }
+
+    pub fn ducking_filter(&self, ducking: &DuckingOptions) -> String {
+        let volume = self.inputs.first().map(|i| i.volume).unwrap_or(1.0);
+        let fade_start = (ducking.duration - ducking.fade_out).max(0.0);
+        format!(
+            "[1:a]{}volume={},atrim=0:{},afade=t=out:st={}:d={}[music];[0:a]asplit=2[voice][sc];[music][sc]sidechaincompress=threshold={}:ratio={}:attack={}:release={}[ducked];[voice][ducked]amix=inputs=2:duration=first:dropout_transition=0:normalize=0[aout]",
+            self.offset_filter(),
+            volume,
+            ducking.duration,
+            fade_start,
+            ducking.fade_out,
+            ducking.threshold,
+            ducking.ratio,
+            ducking.attack_ms,
+            ducking.release_ms
+        )
+    }
+
+    /// Delays the added audio for a positive offset and cuts its start for a negative one.
+    fn offset_filter(&self) -> String {
+        match self.offset_ms {
+            0 => String::new(),
+            ms if ms > 0 => format!("adelay={}:all=1,", ms),
+            ms => format!("atrim=start={},asetpts=PTS-STARTPTS,", -ms as f64 / 1000.0),
+        }
+    }
+
+    pub fn track_metadata_args(&self, existing_tracks: usize) -> String {
+        let mut args = String::new();
+        for (i, input) in self.inputs.iter().enumerate() {
+            let n = existing_tracks + i;
+            if let Some(language) = &input.language {
+                args.push_str(format!(" -metadata:s:a:{} language={}", n, language).as_str());
+            }
+            if let Some(title) = &input.title {
+                let title = string_utils::quote_value(title);
+                args.push_str(format!(" -metadata:s:a:{} title={}", n, title).as_str());
+            }
+        }
+        args
+    }
+}
@@ -0,0 +1,137 @@
+const DITHER_METHODS: [&str; 10] = [
+    "rectangular",
+    "triangular",
+    "triangular_hp",
+    "lipshitz",
+    "shibata",
+    "low_shibata",
+    "high_shibata",
+    "f_weighted",
+    "e_weighted",
+    "modified_e_weighted",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleFormat {
+    S16,
+    S24,
+    S32,
+    F32,
+}
+
+impl SampleFormat {
+    pub fn parse(value: &str) -> Option<SampleFormat> {
+        match value.to_lowercase().as_str() {
+            "16" | "s16" => Some(SampleFormat::S16),
+            "24" | "s24" => Some(SampleFormat::S24),
+            "32" | "s32" => Some(SampleFormat::S32),
+            "f32" | "flt" | "float" => Some(SampleFormat::F32),
+            _ => None,
+        }
+    }
+
+    /// Lossy encoders pick their own, usually planar float, sample format, so only PCM and FLAC
+    /// take one. FLAC has no float samples.
+    pub fn supported_by(&self, codec: &str) -> bool {
+        codec.starts_with("pcm_") || (codec == "flac" && *self != SampleFormat::F32)
+    }
+
+    /// PCM has one codec per bit depth, so the format replaces the codec instead.
+    pub fn pcm_codec(&self) -> &str {
+        match self {
+            SampleFormat::S16 => "pcm_s16le",
+            SampleFormat::S24 => "pcm_s24le",
+            SampleFormat::S32 => "pcm_s32le",
+            SampleFormat::F32 => "pcm_f32le",
+        }
+    }
+
+    /// There is no packed 24 bit sample format, 24 bit audio is stored in 32 bit samples.
+    pub fn as_args(&self) -> &str {
+        match self {
+            SampleFormat::S16 => " -sample_fmt s16",
+            SampleFormat::S24 => " -sample_fmt s32 -bits_per_raw_sample 24",
+            SampleFormat::S32 => " -sample_fmt s32",
+            SampleFormat::F32 => " -sample_fmt flt",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResampleEngine {
+    Swr,
+    Soxr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resampler {
+    pub engine: ResampleEngine,
+    pub dither_method: Option<String>,
+}
+
+impl Resampler {
+    pub fn parse(engine: &str, dither_method: &str) -> Option<Resampler> {
+        let engine = match engine.to_lowercase().as_str() {
+            "" | "swr" => ResampleEngine::Swr,
+            "soxr" => ResampleEngine::Soxr,
+            _ => return None,
+        };
+        let dither_method = match dither_method.to_lowercase() {
+            d if d.is_empty() => None,
+            d if DITHER_METHODS.contains(&d.as_str()) => Some(d),
+            _ => return None,
+        };
+        Some(Resampler {
+            engine,
+            dither_method,
+        })
+    }
+
+    pub fn filter(&self) -> String {
+        let mut filter = match self.engine {
+            ResampleEngine::Swr => String::from("aresample=resampler=swr"),
+            ResampleEngine::Soxr => String::from("aresample=resampler=soxr:precision=28"),
+        };
+        if let Some(dither_method) = &self.dither_method {
+            filter.push_str(format!(":dither_method={}", dither_method).as_str());
+        }
+        filter
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioQuality {
+    /// `-q:a`, e.g. 0 (best) to 9 for libmp3lame or 0.1 to 2 for aac.
+    Vbr(f32),
+    /// Opus is always bitrate driven, its VBR mode uses the audio bitrate as a target.
+    OpusVbr,
+}
+
+impl AudioQuality {
+    pub fn as_args(&self) -> String {
+        match self {
+            AudioQuality::Vbr(quality) => format!(" -q:a {}", quality),
+            AudioQuality::OpusVbr => String::from(" -vbr on"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_resampler() {
+        let resampler = Resampler::parse("soxr", "Triangular").unwrap();
+        assert_eq!(
+            resampler.filter(),
+            "aresample=resampler=soxr:precision=28:dither_method=triangular"
+        );
+        assert_eq!(
+            Resampler::parse("", "").unwrap().filter(),
+            "aresample=resampler=swr"
+        );
+        assert!(Resampler::parse("sinc", "").is_none());
+        assert!(Resampler::parse("swr", "noise").is_none());
+    }
+}
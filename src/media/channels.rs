@@ -0,0 +1,193 @@
+use crate::error::TranscoderError;
+
+/// ITU-R BS.775 gain for the centre and surround channels when folding them into stereo.
+const SURROUND_GAIN: f64 = 0.707;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MonoSource {
+    Left,
+    Right,
+    Both,
+}
+
+#[derive(Debug, Clone)]
+pub enum ChannelOperation {
+    Downmix,
+    /// One output file per channel, in layout order.
+    SplitMono {
+        outputs: Vec<String>,
+    },
+    ToMono(MonoSource),
+    /// Source channel names in the order they should appear in the output.
+    Remap {
+        order: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct ChannelOptions {
+    /// Audio stream index relative to the other audio streams (`0:a:N`).
+    pub stream: usize,
+    pub layout: String,
+    pub operation: ChannelOperation,
+}
+
+impl ChannelOptions {
+    /// The audio filter, or for the split a filter graph with one output label per channel.
+    pub fn filter(&self) -> String {
+        match &self.operation {
+            ChannelOperation::Downmix => downmix_filter(&self.layout),
+            ChannelOperation::SplitMono { .. } => split_filter(self.stream, &self.layout),
+            ChannelOperation::ToMono(MonoSource::Left) => "pan=mono|c0=c0".to_owned(),
+            ChannelOperation::ToMono(MonoSource::Right) => "pan=mono|c0=c1".to_owned(),
+            ChannelOperation::ToMono(MonoSource::Both) => "pan=mono|c0=0.5*c0+0.5*c1".to_owned(),
+            ChannelOperation::Remap { order } => format!(
+                "channelmap=map={}:channel_layout={}",
+                order.join("|"),
+                self.layout
+            ),
+        }
+    }
+}
+
+pub fn layout_channels(layout: &str) -> Option<&'static [&'static str]> {
+    let channels: &[&str] = match layout {
+        "mono" => &["FC"],
+        "stereo" => &["FL", "FR"],
+        "2.1" => &["FL", "FR", "LFE"],
+        "3.0" => &["FL", "FR", "FC"],
+        "4.0" => &["FL", "FR", "FC", "BC"],
+        "quad" => &["FL", "FR", "BL", "BR"],
+        "5.0" => &["FL", "FR", "FC", "BL", "BR"],
+        "5.0(side)" => &["FL", "FR", "FC", "SL", "SR"],
+        "5.1" => &["FL", "FR", "FC", "LFE", "BL", "BR"],
+        "5.1(side)" => &["FL", "FR", "FC", "LFE", "SL", "SR"],
+        "6.1" => &["FL", "FR", "FC", "LFE", "BC", "SL", "SR"],
+        "7.1" => &["FL", "FR", "FC", "LFE", "BL", "BR", "SL", "SR"],
+        _ => return None,
+    };
+    Some(channels)
+}
+
+/// Uses the probed layout, falling back to the default layout for the channel count
+/// when the source does not declare one.
+pub fn resolve_layout(layout: Option<&str>, channels: Option<u8>) -> Option<String> {
+    match layout {
+        Some(layout) if layout_channels(layout).is_some() => Some(layout.to_owned()),
+        _ => match channels? {
+            1 => Some("mono".to_owned()),
+            2 => Some("stereo".to_owned()),
+            6 => Some("5.1".to_owned()),
+            8 => Some("7.1".to_owned()),
+            _ => None,
+        },
+    }
+}
+
+/// Folds every channel into the left and right outputs. The LFE is left out, as in the ITU
+/// downmix, and `<` makes pan renormalize the gains so the sum cannot clip.
+fn downmix_filter(layout: &str) -> String {
+    let channels = layout_channels(layout).unwrap_or_default();
+    let side = |left: bool| {
+        channels
+            .iter()
+            .filter_map(|channel| {
+                let (l, r) = match *channel {
+                    "FL" => (1.0, 0.0),
+                    "FR" => (0.0, 1.0),
+                    "FC" => (SURROUND_GAIN, SURROUND_GAIN),
+                    "BL" | "SL" => (SURROUND_GAIN, 0.0),
+                    "BR" | "SR" => (0.0, SURROUND_GAIN),
+                    "BC" => (0.5, 0.5),
+                    _ => (0.0, 0.0),
+                };
+                let gain = if left { l } else { r };
+                match gain {
+                    0.0 => None,
+                    1.0 => Some(channel.to_string()),
+                    g => Some(format!("{}*{}", g, channel)),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("+")
+    };
+    format!("pan=stereo|FL<{}|FR<{}", side(true), side(false))
+}
+
+fn split_filter(stream: usize, layout: &str) -> String {
+    let labels = layout_channels(layout)
+        .unwrap_or_default()
+        .iter()
+        .map(|channel| format!("[{}]", channel))
+        .collect::<String>();
+    format!(
+        "[0:a:{}]channelsplit=channel_layout={}{}",
+        stream, layout, labels
+    )
+}
+
+/// Parses a comma separated channel order (e.g. `FR,FL`). Every channel must exist in the
+/// layout and the count must match, since the output keeps the source layout.
+pub fn parse_channel_order(value: &str, layout: &str) -> Result<Vec<String>, TranscoderError> {
+    let channels = layout_channels(layout).unwrap_or_default();
+    let order = value
+        .split(',')
+        .map(|c| c.trim().to_uppercase())
+        .filter(|c| !c.is_empty())
+        .collect::<Vec<_>>();
+    if order.len() != channels.len() {
+        return Err(TranscoderError::InvalidList(value.to_owned()));
+    }
+    match order.iter().find(|c| !channels.contains(&c.as_str())) {
+        Some(channel) => Err(TranscoderError::InvalidList(channel.to_owned())),
+        None => Ok(order),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(layout: &str, operation: ChannelOperation) -> ChannelOptions {
+        ChannelOptions {
+            stream: 0,
+            layout: layout.to_owned(),
+            operation,
+        }
+    }
+
+    #[test]
+    fn should_build_downmix_matrix() {
+        assert_eq!(
+            options("5.1", ChannelOperation::Downmix).filter(),
+            "pan=stereo|FL<FL+0.707*FC+0.707*BL|FR<FR+0.707*FC+0.707*BR"
+        );
+        assert_eq!(
+            options("7.1", ChannelOperation::Downmix).filter(),
+            "pan=stereo|FL<FL+0.707*FC+0.707*BL+0.707*SL|FR<FR+0.707*FC+0.707*BR+0.707*SR"
+        );
+    }
+
+    #[test]
+    fn should_resolve_layout() {
+        assert_eq!(
+            resolve_layout(Some("5.1(side)"), Some(6)).as_deref(),
+            Some("5.1(side)")
+        );
+        assert_eq!(
+            resolve_layout(Some("unknown"), Some(2)).as_deref(),
+            Some("stereo")
+        );
+        assert_eq!(resolve_layout(None, Some(3)), None);
+    }
+
+    #[test]
+    fn should_parse_channel_order() {
+        assert_eq!(
+            parse_channel_order("fr, FL", "stereo").unwrap(),
+            vec!["FR", "FL"]
+        );
+        assert!(parse_channel_order("FR", "stereo").is_err());
+        assert!(parse_channel_order("FR,BL", "stereo").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use crate::error::TranscoderError;
+use crate::string_utils;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Chapter {
+    pub start: f64,
+    pub end: f64,
+    pub title: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChapterFile {
+    pub chapter: Chapter,
+    pub output_file: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum ChapterOperation {
+    Write { metadata_file: String },
+    Split { outputs: Vec<ChapterFile> },
+}
+
+/// Parses lines of `timestamp title` or `timestamp,title`. Each chapter ends where the next one starts,
+/// the last one at the end of the file.
+pub fn parse_chapter_list(content: &str, duration: f64) -> Result<Vec<Chapter>, TranscoderError> {
+    let mut chapters: Vec<Chapter> = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (timestamp, title) = match line.split_once(',') {
+            Some((t, title)) if string_utils::parse_timestamp(t).is_ok() => (t, title),
+            _ => line.split_once(char::is_whitespace).unwrap_or((line, "")),
+        };
+        let start = string_utils::parse_timestamp(timestamp)
+            .map_err(|_| TranscoderError::InvalidList(line.to_owned()))?;
+        if start >= duration || chapters.last().is_some_and(|c| c.start >= start) {
+            return Err(TranscoderError::InvalidList(line.to_owned()));
+        }
+        if let Some(previous) = chapters.last_mut() {
+            previous.end = start;
+        }
+        let title = match title.trim() {
+            "" => format!("Chapter {}", chapters.len() + 1),
+            t => t.to_owned(),
+        };
+        chapters.push(Chapter {
+            start,
+            end: duration,
+            title,
+        });
+    }
+    Ok(chapters)
+}
+
+pub fn to_ffmetadata(chapters: &[Chapter]) -> String {
+    let mut metadata = String::from(";FFMETADATA1\n");
+    for chapter in chapters {
+        metadata.push_str(
+            format!(
+                "[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+                (chapter.start * 1000.0).round() as u64,
+                (chapter.end * 1000.0).round() as u64,
+                escape_ffmetadata(&chapter.title)
+            )
+            .as_str(),
+        );
+    }
+    metadata
+}
+
+fn escape_ffmetadata(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Turns a chapter title into something safe to use in a file name.
+pub fn file_name_part(title: &str) -> String {
+    let name = title
+        .trim()
+        .chars()
+        .map(|c| match c.is_alphanumeric() || c == '-' {
+            true => c,
+            false => '_',
+        })
+        .collect::<String>();
+    let name = name.trim_matches('_');
+    match name.is_empty() {
+        true => "chapter".to_owned(),
+        false => name.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_chapter_list() {
+        let content = "# agenda\n00:00:00 Intro\n00:05:00,Budget, Q3\n\n00:20:00\n";
+        let chapters = parse_chapter_list(content, 1800.0).unwrap();
+        assert_eq!(
+            chapters,
+            vec![
+                Chapter {
+                    start: 0.0,
+                    end: 300.0,
+                    title: "Intro".to_owned()
+                },
+                Chapter {
+                    start: 300.0,
+                    end: 1200.0,
+                    title: "Budget, Q3".to_owned()
+                },
+                Chapter {
+                    start: 1200.0,
+                    end: 1800.0,
+                    title: "Chapter 3".to_owned()
+                },
+            ]
+        );
+
+        assert!(parse_chapter_list("00:10:00 B\n00:05:00 A", 1800.0).is_err());
+        assert!(parse_chapter_list("01:00:00 Too late", 1800.0).is_err());
+    }
+
+    #[test]
+    fn should_write_ffmetadata() {
+        let chapters = vec![Chapter {
+            start: 1.5,
+            end: 60.0,
+            title: "Q&A; part=1".to_owned(),
+        }];
+        assert_eq!(
+            to_ffmetadata(&chapters),
+            ";FFMETADATA1\n[CHAPTER]\nTIMEBASE=1/1000\nSTART=1500\nEND=60000\ntitle=Q&A\\; part\\=1\n"
+        );
+    }
+
+    #[test]
+    fn should_create_file_name_part() {
+        assert_eq!(file_name_part("Budget: Q3/Q4"), "Budget__Q3_Q4");
+        assert_eq!(file_name_part("???"), "chapter");
+    }
+}
@@ -0,0 +1,135 @@
+use crate::command::command_runner;
+use crate::string_utils;
+use std::fmt::{Display, Formatter};
+
+/// Points of the file sampled with cropdetect. Several points keep dark scenes or title cards
+/// from deciding the crop on their own.
+pub const SAMPLE_COUNT: usize = 10;
+const FRAMES_PER_SAMPLE: u32 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crop {
+    pub width: u32,
+    pub height: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Crop {
+    /// Parses `w:h:x:y`, as printed by cropdetect after `crop=`.
+    pub fn parse(value: &str) -> Option<Crop> {
+        let parts = value
+            .split(':')
+            .map(|p| p.trim().parse::<u32>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        match parts.as_slice() {
+            [width, height, x, y] if *width > 0 && *height > 0 => Some(Crop {
+                width: *width,
+                height: *height,
+                x: *x,
+                y: *y,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn filter(&self) -> String {
+        format!("crop={}:{}:{}:{}", self.width, self.height, self.x, self.y)
+    }
+}
+
+impl Display for Crop {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        format!("{}x{} at {},{}", self.width, self.height, self.x, self.y).fmt(f)
+    }
+}
+
+/// Evenly spread points, leaving out the very start and end where logos and credits live.
+pub fn sample_points(duration: f64, count: usize) -> Vec<f64> {
+    (1..=count)
+        .map(|i| duration * i as f64 / (count + 1) as f64)
+        .collect()
+}
+
+/// Runs cropdetect at every sample point. Returns the winning crop with the number of
+/// samples that agreed on it.
+pub fn detect_crop(path: &str, duration: f64) -> Option<(Crop, usize)> {
+    let crops = sample_points(duration, SAMPLE_COUNT)
+        .into_iter()
+        .filter_map(|point| {
+            let cmd = format!(
+                "ffmpeg -hide_banner -nostats -ss {} -i {} -map 0:v:0 -vf cropdetect=limit=24:round=2 -frames:v {} -an -f null -",
+                point,
+                string_utils::wrap_with_quotes_if_whitespace(path),
+                FRAMES_PER_SAMPLE
+            );
+            parse_cropdetect(&command_runner::execute_cmd_get_stderr_lines(&cmd))
+        })
+        .collect::<Vec<_>>();
+    majority(&crops)
+}
+
+/// cropdetect refines its guess frame by frame, so the last reported value is used.
+fn parse_cropdetect(lines: &[String]) -> Option<Crop> {
+    lines
+        .iter()
+        .rev()
+        .filter(|l| l.contains("cropdetect"))
+        .find_map(|l| l.rsplit_once("crop=").and_then(|(_, v)| Crop::parse(v)))
+}
+
+/// The most frequent crop. Ties go to the larger rectangle, so picture is never cut off
+/// because of a dark scene.
+fn majority(crops: &[Crop]) -> Option<(Crop, usize)> {
+    let mut votes: Vec<(Crop, usize)> = Vec::new();
+    for crop in crops {
+        match votes.iter_mut().find(|(c, _)| c == crop) {
+            Some((_, count)) => *count += 1,
+            None => votes.push((*crop, 1)),
+        }
+    }
+    votes
+        .into_iter()
+        .max_by_key(|(crop, count)| (*count, crop.width * crop.height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crop(width: u32, height: u32, x: u32, y: u32) -> Crop {
+        Crop {
+            width,
+            height,
+            x,
+            y,
+        }
+    }
+
+    #[test]
+    fn should_parse_cropdetect_output() {
+        let lines = [
+            "[Parsed_cropdetect_0 @ 0x55e1] x1:0 x2:1919 y1:138 y2:941 w:1920 h:800 x:0 y:140 pts:1 t:0.04 limit:0.09 crop=1920:800:0:140",
+            "[Parsed_cropdetect_0 @ 0x55e1] x1:0 x2:1919 y1:136 y2:943 w:1920 h:804 x:0 y:138 pts:2 t:0.08 limit:0.09 crop=1920:804:0:138",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect::<Vec<_>>();
+        assert_eq!(parse_cropdetect(&lines), Some(crop(1920, 804, 0, 138)));
+        assert_eq!(parse_cropdetect(&[]), None);
+    }
+
+    #[test]
+    fn should_pick_crop_by_majority() {
+        let wide = crop(1920, 800, 0, 140);
+        let dark = crop(1280, 540, 320, 270);
+        assert_eq!(majority(&[wide, dark, wide]), Some((wide, 2)));
+        assert_eq!(majority(&[dark, wide]), Some((wide, 1)));
+        assert_eq!(majority(&[]), None);
+    }
+
+    #[test]
+    fn should_spread_sample_points() {
+        assert_eq!(sample_points(40.0, 3), vec![10.0, 20.0, 30.0]);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::error::TranscoderError;
+use crate::string_utils::{self, Timecode};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clip {
+    pub source: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+#[derive(Debug, Clone)]
+pub enum CutMode {
+    /// Stream copies every range (cuts snap to keyframes) and joins them with the concat demuxer.
+    Lossless {
+        parts: Vec<String>,
+        list_file: String,
+    },
+    /// Re-encodes everything with trim/atrim and the concat filter. All sources need the same resolution.
+    /// The audio is only kept when every source has some.
+    FrameAccurate { has_audio: bool },
+}
+
+#[derive(Debug, Clone)]
+pub struct CutListOptions {
+    pub clips: Vec<Clip>,
+    pub mode: CutMode,
+}
+
+/// Parses CSV lines of `in,out` or `source,in,out`. A header line is skipped.
+pub fn parse_csv(content: &str, default_source: &str) -> Result<Vec<Clip>, TranscoderError> {
+    let mut clips = Vec::new();
+    let lines = content
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'));
+    for (n, line) in lines.enumerate() {
+        let fields = line.split(',').map(|f| f.trim()).collect::<Vec<_>>();
+        let (source, start, end) = match fields.as_slice() {
+            [start, end] => (default_source, *start, *end),
+            [source, start, end] => (*source, *start, *end),
+            _ => return Err(TranscoderError::InvalidList(line.to_owned())),
+        };
+        let range = (
+            string_utils::parse_timestamp(start),
+            string_utils::parse_timestamp(end),
+        );
+        match range {
+            (Ok(start), Ok(end)) => clips.push(Clip {
+                source: source.to_owned(),
+                start,
+                end,
+            }),
+            _ if n == 0 => continue,
+            _ => return Err(TranscoderError::InvalidList(line.to_owned())),
+        }
+    }
+    Ok(clips)
+}
+
+/// Parses CMX3600 event lines, using the source in/out timecodes (`HH:MM:SS:FF`).
+/// A `* FROM CLIP NAME:` comment sets the source of the preceding event. Timecodes are drop
+/// frame after an `FCM: DROP FRAME` line or when they use `;` before the frames.
+pub fn parse_edl(
+    content: &str,
+    default_source: &str,
+    fps: f64,
+) -> Result<Vec<Clip>, TranscoderError> {
+    let mut clips: Vec<Clip> = Vec::new();
+    let mut drop_frame = false;
+    for line in content.lines().map(|l| l.trim()) {
+        if let Some(mode) = line.strip_prefix("FCM:") {
+            drop_frame = mode.trim() == "DROP FRAME";
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("* FROM CLIP NAME:") {
+            if let Some(clip) = clips.last_mut() {
+                clip.source = name.trim().to_owned();
+            }
+            continue;
+        }
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        let is_event = fields.len() >= 8 && fields[0].chars().all(|c| c.is_ascii_digit());
+        if !is_event {
+            continue;
+        }
+        let n = fields.len();
+        let seconds = |value: &str| {
+            Timecode::parse(value).map(|mut timecode| {
+                timecode.drop_frame |= drop_frame;
+                timecode.to_seconds(fps)
+            })
+        };
+        let start = seconds(fields[n - 4]);
+        let end = seconds(fields[n - 3]);
+        match (start, end) {
+            (Some(start), Some(end)) => clips.push(Clip {
+                source: default_source.to_owned(),
+                start,
+                end,
+            }),
+            _ => return Err(TranscoderError::InvalidList(line.to_owned())),
+        }
+    }
+    Ok(clips)
+}
+
+/// Checks that every range is non-empty and lies within the probed duration of its source.
+pub fn validate(clips: &[Clip], durations: &HashMap<String, f64>) -> Result<(), TranscoderError> {
+    if clips.is_empty() {
+        return Err(TranscoderError::InvalidList("empty cut list".to_owned()));
+    }
+    for clip in clips {
+        let duration = durations.get(&clip.source).copied().unwrap_or_default();
+        if clip.start >= clip.end || clip.end > duration {
+            return Err(TranscoderError::InvalidList(format!(
+                "{} {} - {}",
+                clip.source,
+                string_utils::format_timestamp(clip.start),
+                string_utils::format_timestamp(clip.end)
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub fn concat_list(parts: &[String]) -> String {
+    parts
+        .iter()
+        .map(|p| format!("file '{}'\n", p.replace('\'', r"'\''")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(source: &str, start: f64, end: f64) -> Clip {
+        Clip {
+            source: source.to_owned(),
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn should_parse_csv_cut_list() {
+        let csv = "source,in,out\n00:00:10,00:00:20\n/b.mp4, 1:00, 1:30.5\n";
+        let clips = parse_csv(csv, "/a.mp4").unwrap();
+        assert_eq!(
+            clips,
+            vec![clip("/a.mp4", 10.0, 20.0), clip("/b.mp4", 60.0, 90.5)]
+        );
+        assert!(parse_csv("1,2\nx,y", "/a.mp4").is_err());
+    }
+
+    #[test]
+    fn should_parse_edl() {
+        let edl = "TITLE: cuts\nFCM: NON-DROP FRAME\n\n001  AX       V     C        00:00:01:12 00:00:05:00 01:00:00:00 01:00:03:13\n* FROM CLIP NAME: /b.mp4\n002  AX       V     C        00:01:00:00 00:01:02:00 01:00:03:13 01:00:05:13\n";
+        let clips = parse_edl(edl, "/a.mp4", 25.0).unwrap();
+        assert_eq!(
+            clips,
+            vec![clip("/b.mp4", 1.48, 5.0), clip("/a.mp4", 60.0, 62.0)]
+        );
+
+        let edl = "TITLE: cuts\nFCM: DROP FRAME\n\n001  AX       V     C        00:10:00:00 00:10:02:00 01:00:00:00 01:00:02:00\n";
+        let clips = parse_edl(edl, "/a.mp4", 30000.0 / 1001.0).unwrap();
+        assert!((clips[0].start - 600.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn should_validate_ranges() {
+        let durations = HashMap::from([("/a.mp4".to_owned(), 100.0)]);
+        assert!(validate(&[clip("/a.mp4", 10.0, 20.0)], &durations).is_ok());
+        assert!(validate(&[clip("/a.mp4", 90.0, 120.0)], &durations).is_err());
+        assert!(validate(&[clip("/a.mp4", 20.0, 10.0)], &durations).is_err());
+        assert!(validate(&[clip("/b.mp4", 1.0, 2.0)], &durations).is_err());
+    }
+
+    #[test]
+    fn should_create_concat_list() {
+        let parts = vec!["/tmp/part1.mp4".to_owned(), "/tmp/it's.mp4".to_owned()];
+        assert_eq!(
+            concat_list(&parts),
+            "file '/tmp/part1.mp4'\nfile '/tmp/it'\\''s.mp4'\n"
+        );
+    }
+}
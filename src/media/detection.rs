@@ -0,0 +1,421 @@
+use crate::command::command_runner;
+use crate::error::TranscoderError;
+use crate::string_utils;
+
+/// How close to the start or end of the file a detected interval has to be to count as leading
+/// or trailing.
+const EDGE_TOLERANCE: f64 = 0.1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interval {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl Interval {
+    pub fn duration(&self) -> f64 {
+        self.end - self.start
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeepRanges {
+    /// Ranges of the source that are kept, everything in between is dropped.
+    pub keep: Vec<Interval>,
+    has_video: bool,
+    has_audio: bool,
+}
+
+impl KeepRanges {
+    /// Fails when there is no stream to cut or nothing is kept.
+    pub fn new(
+        keep: Vec<Interval>,
+        has_video: bool,
+        has_audio: bool,
+    ) -> Result<KeepRanges, TranscoderError> {
+        if keep.is_empty() || !(has_video || has_audio) {
+            return Err(TranscoderError::UnsupportedOperation);
+        }
+        Ok(KeepRanges {
+            keep,
+            has_video,
+            has_audio,
+        })
+    }
+
+    /// Every range is trimmed on its own and the pieces are joined with `concat`, so video and
+    /// audio restart at the same timestamp after each cut and stay in sync. `video_filters` run
+    /// first on every video piece.
+    pub fn filter(&self, video_filters: &[String]) -> String {
+        let prefix = video_filters
+            .iter()
+            .map(|f| format!("{},", f))
+            .collect::<String>();
+        let mut filter = String::new();
+        let mut pads = String::new();
+        for (i, range) in self.keep.iter().enumerate() {
+            if self.has_video {
+                filter.push_str(&format!(
+                    "[0:v]{}trim=start={}:end={},setpts=PTS-STARTPTS[v{i}];",
+                    prefix, range.start, range.end
+                ));
+                pads.push_str(&format!("[v{i}]"));
+            }
+            if self.has_audio {
+                filter.push_str(&format!(
+                    "[0:a]atrim=start={}:end={},asetpts=PTS-STARTPTS[a{i}];",
+                    range.start, range.end
+                ));
+                pads.push_str(&format!("[a{i}]"));
+            }
+        }
+        let outputs = match (self.has_video, self.has_audio) {
+            (true, true) => "[v][a]",
+            (true, false) => "[v]",
+            (false, _) => "[a]",
+        };
+        format!(
+            "{}{}concat=n={}:v={}:a={}{}",
+            filter,
+            pads,
+            self.keep.len(),
+            self.has_video as u8,
+            self.has_audio as u8,
+            outputs
+        )
+    }
+
+    pub fn maps(&self) -> &str {
+        match (self.has_video, self.has_audio) {
+            (true, true) => r#" -map "[v]" -map "[a]""#,
+            (true, false) => r#" -map "[v]""#,
+            (false, _) => r#" -map "[a]""#,
+        }
+    }
+}
+
+pub fn detect_silence(
+    path: &str,
+    noise_db: f64,
+    min_duration: f64,
+    duration: f64,
+) -> Vec<Interval> {
+    let cmd = format!(
+        "ffmpeg -hide_banner -nostats -i {} -map 0:a:0 -af silencedetect=noise={}dB:d={} -f null -",
+        string_utils::wrap_with_quotes_if_whitespace(path),
+        noise_db,
+        min_duration
+    );
+    parse_silence(
+        &command_runner::execute_cmd_get_stderr_lines(&cmd),
+        duration,
+    )
+}
+
+/// Parses the `silence_start` / `silence_end` log lines. A silence still open at the end
+/// of the file lasts until `duration`.
+fn parse_silence(lines: &[String], duration: f64) -> Vec<Interval> {
+    let mut silences = Vec::new();
+    let mut start = None;
+    for line in lines.iter().filter(|l| l.contains("silencedetect")) {
+        if let Some(value) = value_after(line, "silence_start:") {
+            start = Some(value.max(0.0));
+        } else if let Some(end) = value_after(line, "silence_end:") {
+            if let Some(start) = start.take() {
+                silences.push(Interval { start, end });
+            }
+        }
+    }
+    if let Some(start) = start {
+        silences.push(Interval {
+            start,
+            end: duration,
+        });
+    }
+    silences
+}
+
+fn value_after(line: &str, key: &str) -> Option<f64> {
+    let (_, rest) = line.split_once(key)?;
+    rest.split_whitespace().next()?.parse().ok()
+}
+
+pub fn detect_black(path: &str, min_duration: f64, pixel_threshold: f64) -> Vec<Interval> {
+    let cmd = format!(
+        "ffmpeg -hide_banner -nostats -i {} -map 0:v:0 -vf blackdetect=d={}:pix_th={} -an -f null -",
+        string_utils::wrap_with_quotes_if_whitespace(path),
+        min_duration,
+        pixel_threshold
+    );
+    parse_black(&command_runner::execute_cmd_get_stderr_lines(&cmd))
+}
+
+/// Parses `black_start:0 black_end:2.04 black_duration:2.04` log lines.
+fn parse_black(lines: &[String]) -> Vec<Interval> {
+    lines
+        .iter()
+        .filter(|l| l.contains("blackdetect"))
+        .filter_map(|line| {
+            Some(Interval {
+                start: value_after(line, "black_start:")?,
+                end: value_after(line, "black_end:")?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the times of frames whose scene change score is above `threshold` (0 - 1).
+pub fn detect_scenes(path: &str, threshold: f64) -> Vec<f64> {
+    let cmd = format!(
+        r#"ffmpeg -hide_banner -nostats -i {} -map 0:v:0 -vf "select='gt(scene,{})',showinfo" -an -f null -"#,
+        string_utils::wrap_with_quotes_if_whitespace(path),
+        threshold
+    );
+    parse_scenes(&command_runner::execute_cmd_get_stderr_lines(&cmd))
+}
+
+/// Parses the `pts_time` of the frames showinfo reports.
+fn parse_scenes(lines: &[String]) -> Vec<f64> {
+    lines
+        .iter()
+        .filter(|l| l.contains("Parsed_showinfo"))
+        .filter_map(|line| value_after(line, "pts_time:"))
+        .filter(|t| *t > 0.0)
+        .collect()
+}
+
+/// Turns scene cuts into the ranges between them.
+pub fn scene_ranges(cuts: &[f64], duration: f64) -> Vec<Interval> {
+    let mut ranges = Vec::new();
+    let mut start = 0.0;
+    for cut in cuts.iter().copied().chain(std::iter::once(duration)) {
+        if cut > start {
+            ranges.push(Interval { start, end: cut });
+            start = cut;
+        }
+    }
+    ranges
+}
+
+/// The range left after trimming leading and trailing black. When `silence` is given only the
+/// part that is both black and silent is trimmed, so an intro playing over black is kept.
+pub fn trim_range(black: &[Interval], silence: Option<&[Interval]>, duration: f64) -> Interval {
+    let mut start = leading_end(black);
+    let mut end = trailing_start(black, duration);
+    if let Some(silence) = silence {
+        start = start.min(leading_end(silence));
+        end = end.max(trailing_start(silence, duration));
+    }
+    Interval { start, end }
+}
+
+fn leading_end(intervals: &[Interval]) -> f64 {
+    let mut end = 0.0;
+    for interval in intervals {
+        if interval.start > end + EDGE_TOLERANCE {
+            break;
+        }
+        end = interval.end.max(end);
+    }
+    end
+}
+
+fn trailing_start(intervals: &[Interval], duration: f64) -> f64 {
+    let mut start = duration;
+    for interval in intervals.iter().rev() {
+        if interval.end < start - EDGE_TOLERANCE {
+            break;
+        }
+        start = interval.start.min(start);
+    }
+    start
+}
+
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    pub duration: f64,
+    pub scenes: Vec<Interval>,
+    pub black: Vec<Interval>,
+    pub silence: Vec<Interval>,
+}
+
+impl Timeline {
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Duration: {}\n",
+            string_utils::format_timestamp(self.duration)
+        );
+        let sections = [
+            ("Scenes", &self.scenes),
+            ("Black", &self.black),
+            ("Silence", &self.silence),
+        ];
+        for (name, intervals) in sections {
+            text.push_str(format!("{} ({}):\n", name, intervals.len()).as_str());
+            for (i, interval) in intervals.iter().enumerate() {
+                text.push_str(
+                    format!(
+                        "{:>4}. {} - {} ({:.2}s)\n",
+                        i + 1,
+                        string_utils::format_timestamp(interval.start),
+                        string_utils::format_timestamp(interval.end),
+                        interval.duration()
+                    )
+                    .as_str(),
+                );
+            }
+        }
+        text
+    }
+
+    pub fn to_json(&self) -> String {
+        let intervals = |intervals: &[Interval]| {
+            intervals
+                .iter()
+                .map(|i| format!(r#"{{"start":{},"end":{}}}"#, i.start, i.end))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        format!(
+            r#"{{"duration":{},"scenes":[{}],"black":[{}],"silence":[{}]}}"#,
+            self.duration,
+            intervals(&self.scenes),
+            intervals(&self.black),
+            intervals(&self.silence)
+        )
+    }
+}
+
+/// Shortens every silence longer than `max_gap` to `max_gap`, keeping half of it on each side.
+/// A `max_gap` of 0 removes silences completely.
+pub fn keep_ranges(silences: &[Interval], duration: f64, max_gap: f64) -> Vec<Interval> {
+    let mut ranges = Vec::new();
+    let mut cursor = 0.0;
+    for silence in silences.iter().filter(|s| s.duration() > max_gap) {
+        let cut_start = silence.start + max_gap / 2.0;
+        if cut_start > cursor {
+            ranges.push(Interval {
+                start: cursor,
+                end: cut_start,
+            });
+        }
+        cursor = silence.end - max_gap / 2.0;
+    }
+    if cursor < duration {
+        ranges.push(Interval {
+            start: cursor,
+            end: duration,
+        });
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(start: f64, end: f64) -> Interval {
+        Interval { start, end }
+    }
+
+    #[test]
+    fn should_parse_silence_intervals() {
+        let lines = [
+            "[silencedetect @ 0x55d1c] silence_start: -0.0123",
+            "[silencedetect @ 0x55d1c] silence_end: 2.5 | silence_duration: 2.51",
+            "size=N/A time=00:00:30.00 bitrate=N/A speed= 500x",
+            "[silencedetect @ 0x55d1c] silence_start: 20",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect::<Vec<_>>();
+        assert_eq!(
+            parse_silence(&lines, 30.0),
+            vec![interval(0.0, 2.5), interval(20.0, 30.0)]
+        );
+    }
+
+    #[test]
+    fn should_parse_black_and_scenes() {
+        let lines = [
+            "[blackdetect @ 0x5581] black_start:0 black_end:2.04 black_duration:2.04",
+            "[Parsed_showinfo_1 @ 0x5582] n:   0 pts:  52736 pts_time:4.12    duration:512",
+            "[Parsed_showinfo_1 @ 0x5582] n:   1 pts: 384000 pts_time:30      duration:512",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect::<Vec<_>>();
+        assert_eq!(parse_black(&lines), vec![interval(0.0, 2.04)]);
+        assert_eq!(parse_scenes(&lines), vec![4.12, 30.0]);
+        assert_eq!(
+            scene_ranges(&[4.12, 30.0], 40.0),
+            vec![
+                interval(0.0, 4.12),
+                interval(4.12, 30.0),
+                interval(30.0, 40.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_trim_black_that_is_also_silent() {
+        let black = [
+            interval(0.0, 3.0),
+            interval(12.0, 13.0),
+            interval(57.0, 60.0),
+        ];
+        let silence = [interval(0.05, 2.0), interval(56.0, 60.0)];
+        assert_eq!(trim_range(&black, None, 60.0), interval(3.0, 57.0));
+        assert_eq!(
+            trim_range(&black, Some(&silence), 60.0),
+            interval(2.0, 57.0)
+        );
+    }
+
+    #[test]
+    fn should_write_timeline_json() {
+        let timeline = Timeline {
+            duration: 40.0,
+            scenes: vec![interval(0.0, 4.12), interval(4.12, 40.0)],
+            black: vec![interval(0.0, 1.5)],
+            silence: Vec::new(),
+        };
+        assert_eq!(
+            timeline.to_json(),
+            r#"{"duration":40,"scenes":[{"start":0,"end":4.12},{"start":4.12,"end":40}],"black":[{"start":0,"end":1.5}],"silence":[]}"#
+        );
+    }
+
+    #[test]
+    fn should_trim_and_concat_kept_ranges() {
+        let audio_only =
+            KeepRanges::new(vec![interval(0.0, 4.5), interval(9.5, 20.0)], false, true).unwrap();
+        assert_eq!(
+            audio_only.filter(&[]),
+            "[0:a]atrim=start=0:end=4.5,asetpts=PTS-STARTPTS[a0];[0:a]atrim=start=9.5:end=20,asetpts=PTS-STARTPTS[a1];[a0][a1]concat=n=2:v=0:a=1[a]"
+        );
+        assert!(KeepRanges::new(vec![interval(0.0, 4.5)], false, false).is_err());
+        assert!(KeepRanges::new(Vec::new(), true, true).is_err());
+    }
+
+    #[test]
+    fn should_calculate_kept_ranges() {
+        let silences = [
+            interval(0.0, 2.0),
+            interval(10.0, 10.5),
+            interval(20.0, 30.0),
+        ];
+        assert_eq!(
+            keep_ranges(&silences, 40.0, 1.0),
+            vec![
+                interval(0.0, 0.5),
+                interval(1.5, 20.5),
+                interval(29.5, 40.0)
+            ]
+        );
+        assert_eq!(
+            keep_ranges(&silences, 30.0, 0.0),
+            vec![interval(2.0, 10.0), interval(10.5, 20.0)]
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use crate::command::command_runner;
+use crate::string_utils;
+
+/// Seconds of packets read for the analysis, enough to catch dropped or doubled frames.
+const ANALYZED_SECONDS: u32 = 60;
+/// A frame interval further than this from the nominal one counts as irregular.
+const INTERVAL_TOLERANCE: f64 = 0.1;
+/// Share of irregular intervals above which the stream is treated as variable frame rate.
+const VFR_THRESHOLD: f64 = 0.02;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameTiming {
+    pub nominal: f64,
+    pub average: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Share of frame intervals that differ from the nominal one.
+    pub irregular: f64,
+}
+
+impl FrameTiming {
+    pub fn is_variable(&self) -> bool {
+        self.irregular > VFR_THRESHOLD
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RateChange {
+    /// Drops or duplicates frames, which also turns variable into constant frame rate.
+    Fps(String),
+    /// Synthesizes the new frames with motion compensation instead of repeating them. Slow to
+    /// encode, but smooth. The duration stays the same, so this is no slow motion.
+    Interpolate(String),
+}
+
+impl RateChange {
+    pub fn filter(&self) -> String {
+        match self {
+            RateChange::Fps(rate) => format!("fps={}", rate),
+            RateChange::Interpolate(rate) => format!(
+                "minterpolate=fps={}:mi_mode=mci:mc_mode=aobmc:me_mode=bidir:vsbmc=1",
+                rate
+            ),
+        }
+    }
+}
+
+/// Accepts a decimal (`25`, `29.97`) or a rational (`30000/1001`) frame rate.
+pub fn parse_rate(value: &str) -> Option<String> {
+    let rate = match value.split_once('/') {
+        Some((num, den)) => num.parse::<f64>().ok()? / den.parse::<f64>().ok()?,
+        None => value.parse::<f64>().ok()?,
+    };
+    match rate.is_finite() && rate > 0.0 && rate <= 1000.0 {
+        true => Some(value.to_owned()),
+        false => None,
+    }
+}
+
+pub fn probe_timestamps(path: &str) -> Vec<f64> {
+    let cmd = format!(
+        "ffprobe -v error -select_streams v:0 -read_intervals %+{} -show_entries packet=pts_time -of csv=print_section=0 {}",
+        ANALYZED_SECONDS,
+        string_utils::wrap_with_quotes_if_whitespace(path)
+    );
+    let mut timestamps = command_runner::execute_cmd_get_lines(&cmd)
+        .iter()
+        .filter_map(|line| line.trim().trim_end_matches(',').parse::<f64>().ok())
+        .collect::<Vec<_>>();
+    // packets come in decode order, B-frames make them jump back and forth
+    timestamps.sort_by(|a, b| a.total_cmp(b));
+    timestamps
+}
+
+/// Compares the intervals between sorted frame timestamps with the nominal frame rate.
+pub fn analyze(timestamps: &[f64], nominal: f64) -> Option<FrameTiming> {
+    let intervals = timestamps
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .filter(|interval| *interval > 0.0)
+        .collect::<Vec<_>>();
+    if intervals.is_empty() || nominal <= 0.0 {
+        return None;
+    }
+    let expected = 1.0 / nominal;
+    let irregular = intervals
+        .iter()
+        .filter(|interval| (*interval - expected).abs() > expected * INTERVAL_TOLERANCE)
+        .count();
+    let longest = intervals.iter().copied().fold(f64::MIN, f64::max);
+    let shortest = intervals.iter().copied().fold(f64::MAX, f64::min);
+    let span = timestamps[timestamps.len() - 1] - timestamps[0];
+    Some(FrameTiming {
+        nominal,
+        average: intervals.len() as f64 / span,
+        min: 1.0 / longest,
+        max: 1.0 / shortest,
+        irregular: irregular as f64 / intervals.len() as f64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_constant_frame_rate() {
+        let timestamps = (0..100).map(|i| i as f64 * 0.04).collect::<Vec<_>>();
+        let timing = analyze(&timestamps, 25.0).unwrap();
+        assert!(!timing.is_variable());
+        assert!((timing.average - 25.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn should_detect_variable_frame_rate() {
+        // a screen capture that drops to 10 fps while nothing moves
+        let mut timestamps = (0..50).map(|i| i as f64 / 30.0).collect::<Vec<_>>();
+        timestamps.extend((1..=10).map(|i| 49.0 / 30.0 + i as f64 * 0.1));
+        let timing = analyze(&timestamps, 30.0).unwrap();
+        assert!(timing.is_variable());
+        assert!((timing.min - 10.0).abs() < 0.001);
+        assert!((timing.max - 30.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn should_parse_rate() {
+        assert_eq!(parse_rate("30000/1001").as_deref(), Some("30000/1001"));
+        assert_eq!(parse_rate("29.97").as_deref(), Some("29.97"));
+        assert_eq!(parse_rate("0"), None);
+        assert_eq!(parse_rate("1/0"), None);
+        assert_eq!(parse_rate("fast"), None);
+    }
+}
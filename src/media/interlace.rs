@@ -0,0 +1,194 @@
+use crate::command::command_runner;
+use crate::string_utils;
+use std::fmt::{Display, Formatter};
+
+const ANALYZED_FRAMES: u32 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScanType {
+    Progressive,
+    TopFieldFirst,
+    BottomFieldFirst,
+    /// Film converted to video rate with 3:2 pulldown.
+    Telecined,
+}
+
+impl ScanType {
+    pub fn parity(&self) -> &str {
+        match self {
+            ScanType::TopFieldFirst => "tff",
+            ScanType::BottomFieldFirst => "bff",
+            _ => "auto",
+        }
+    }
+}
+
+impl Display for ScanType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScanType::Progressive => f.write_str("progressive"),
+            ScanType::TopFieldFirst => f.write_str("interlaced, top field first"),
+            ScanType::BottomFieldFirst => f.write_str("interlaced, bottom field first"),
+            ScanType::Telecined => f.write_str("telecined"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Deinterlacer {
+    Bwdif,
+    Yadif,
+    InverseTelecine,
+}
+
+impl Deinterlacer {
+    pub fn parse(value: &str) -> Option<Deinterlacer> {
+        match value.to_lowercase().as_str() {
+            "bwdif" => Some(Deinterlacer::Bwdif),
+            "yadif" => Some(Deinterlacer::Yadif),
+            "ivtc" => Some(Deinterlacer::InverseTelecine),
+            _ => None,
+        }
+    }
+
+    pub fn for_scan(scan: ScanType) -> Option<Deinterlacer> {
+        match scan {
+            ScanType::Progressive => None,
+            ScanType::TopFieldFirst | ScanType::BottomFieldFirst => Some(Deinterlacer::Bwdif),
+            ScanType::Telecined => Some(Deinterlacer::InverseTelecine),
+        }
+    }
+
+    /// Frame rate is kept (one frame per frame). Inverse telecine matches the fields back into
+    /// film frames, deinterlaces what could not be matched and drops the duplicate frame.
+    pub fn filter(&self, parity: &str) -> String {
+        match self {
+            Deinterlacer::Bwdif => format!("bwdif=mode=send_frame:parity={}:deint=all", parity),
+            Deinterlacer::Yadif => format!("yadif=mode=send_frame:parity={}:deint=all", parity),
+            Deinterlacer::InverseTelecine => format!(
+                "fieldmatch=order={}:combmatch=full,yadif=deint=interlaced,decimate",
+                parity
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct IdetCounts {
+    tff: u32,
+    bff: u32,
+    progressive: u32,
+    undetermined: u32,
+    repeated: u32,
+    not_repeated: u32,
+}
+
+impl IdetCounts {
+    /// Pulldown leaves two combed frames and a repeated field in every five frames, so a
+    /// telecined source shows a mix of interlaced and progressive frames plus repeated fields.
+    fn classify(&self) -> ScanType {
+        let interlaced = self.tff + self.bff;
+        let total = (interlaced + self.progressive + self.undetermined).max(1) as f64;
+        let interlaced_share = interlaced as f64 / total;
+        let repeated_share =
+            self.repeated as f64 / (self.repeated + self.not_repeated).max(1) as f64;
+        if (0.2..0.7).contains(&interlaced_share) && repeated_share > 0.1 {
+            ScanType::Telecined
+        } else if interlaced > self.progressive && self.tff >= self.bff {
+            ScanType::TopFieldFirst
+        } else if interlaced > self.progressive {
+            ScanType::BottomFieldFirst
+        } else {
+            ScanType::Progressive
+        }
+    }
+}
+
+pub fn detect(path: &str) -> Option<ScanType> {
+    let cmd = format!(
+        "ffmpeg -hide_banner -nostats -i {} -map 0:v:0 -vf idet -frames:v {} -an -f null -",
+        string_utils::wrap_with_quotes_if_whitespace(path),
+        ANALYZED_FRAMES
+    );
+    parse_idet(&command_runner::execute_cmd_get_stderr_lines(&cmd)).map(|c| c.classify())
+}
+
+/// Uses the multi frame detection summary, it is more reliable than the single frame one.
+fn parse_idet(lines: &[String]) -> Option<IdetCounts> {
+    let mut counts = IdetCounts::default();
+    let mut found = false;
+    for line in lines.iter().filter(|l| l.contains("Parsed_idet")) {
+        let values = line.split_whitespace().collect::<Vec<_>>();
+        let value = |key: &str| {
+            values
+                .iter()
+                .position(|v| *v == key)
+                .and_then(|i| values.get(i + 1))
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(0)
+        };
+        if line.contains("Multi frame detection:") {
+            counts.tff = value("TFF:");
+            counts.bff = value("BFF:");
+            counts.progressive = value("Progressive:");
+            counts.undetermined = value("Undetermined:");
+            found = true;
+        } else if line.contains("Repeated Fields:") {
+            counts.repeated = value("Top:") + value("Bottom:");
+            counts.not_repeated = value("Neither:");
+        }
+    }
+    found.then_some(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idet(repeated: &str, multi: &str) -> Vec<String> {
+        vec![
+            format!("[Parsed_idet_0 @ 0x5611] Repeated Fields: {}", repeated),
+            "[Parsed_idet_0 @ 0x5611] Single frame detection: TFF: 1 BFF: 0 Progressive: 2 Undetermined: 497".to_owned(),
+            format!("[Parsed_idet_0 @ 0x5611] Multi frame detection: {}", multi),
+        ]
+    }
+
+    #[test]
+    fn should_classify_scan_type() {
+        let classify = |lines: Vec<String>| parse_idet(&lines).unwrap().classify();
+        assert_eq!(
+            classify(idet(
+                "Neither: 499 Top: 0 Bottom: 1",
+                "TFF: 480 BFF: 0 Progressive: 5 Undetermined: 15"
+            )),
+            ScanType::TopFieldFirst
+        );
+        assert_eq!(
+            classify(idet(
+                "Neither: 500 Top: 0 Bottom: 0",
+                "TFF: 0 BFF: 2 Progressive: 490 Undetermined: 8"
+            )),
+            ScanType::Progressive
+        );
+        assert_eq!(
+            classify(idet(
+                "Neither: 400 Top: 50 Bottom: 50",
+                "TFF: 200 BFF: 0 Progressive: 290 Undetermined: 10"
+            )),
+            ScanType::Telecined
+        );
+        assert_eq!(parse_idet(&[]), None);
+    }
+
+    #[test]
+    fn should_build_deinterlace_filters() {
+        assert_eq!(
+            Deinterlacer::Bwdif.filter(ScanType::BottomFieldFirst.parity()),
+            "bwdif=mode=send_frame:parity=bff:deint=all"
+        );
+        assert_eq!(
+            Deinterlacer::InverseTelecine.filter(ScanType::Telecined.parity()),
+            "fieldmatch=order=auto:combmatch=full,yadif=deint=interlaced,decimate"
+        );
+    }
+}
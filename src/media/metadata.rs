@@ -0,0 +1,157 @@
+use crate::media::probe::MediaInfo;
+use crate::string_utils;
+
+pub const EDITABLE_TAGS: [&str; 4] = ["title", "artist", "comment", "creation_time"];
+
+/// Tags written by the muxers themselves, they are expected to survive stripping.
+const TECHNICAL_TAGS: [&str; 7] = [
+    "major_brand",
+    "minor_version",
+    "compatible_brands",
+    "encoder",
+    "handler_name",
+    "vendor_id",
+    "language",
+];
+
+#[derive(Debug, Clone)]
+pub enum MetadataEdit {
+    Set {
+        tags: Vec<(String, String)>,
+        language: Option<String>,
+    },
+    StripAll,
+}
+
+impl MetadataEdit {
+    pub fn as_args(&self) -> String {
+        let mut args = String::new();
+        match self {
+            MetadataEdit::Set { tags, language } => {
+                for (key, value) in tags {
+                    let value = string_utils::quote_value(value);
+                    args.push_str(format!(" -metadata {}={}", key, value).as_str());
+                }
+                if let Some(language) = language {
+                    args.push_str(format!(" -metadata:s:a language={}", language).as_str());
+                    args.push_str(format!(" -metadata:s:s language={}", language).as_str());
+                }
+            }
+            MetadataEdit::StripAll => {
+                // global tags hold the GPS location atoms of phone videos, subtitle streams
+                // often carry titles and author names, data streams timed GPS tracks
+                args.push_str(" -map -0:d -map_metadata -1 -map_metadata:s -1");
+                args.push_str(" -map_chapters -1 -fflags +bitexact");
+            }
+        }
+        args
+    }
+
+    /// Compares the re-probed output with the requested edit and describes every mismatch.
+    pub fn verify(&self, info: &MediaInfo) -> Vec<String> {
+        let mut problems = Vec::new();
+        match self {
+            MetadataEdit::Set { tags, language } => {
+                for (key, value) in tags {
+                    match info.tags.get(key) {
+                        Some(v) if v == value || key == "creation_time" => {}
+                        Some(v) => problems.push(format!("Tag {} is '{}'.", key, v)),
+                        None => problems.push(format!("Tag {} is missing.", key)),
+                    }
+                }
+                if let Some(language) = language {
+                    let streams = info
+                        .streams_of_type("audio")
+                        .chain(info.streams_of_type("subtitle"));
+                    for stream in streams {
+                        if stream.language() != Some(language) {
+                            problems.push(format!("Stream #{} has no language set.", stream.index));
+                        }
+                    }
+                }
+            }
+            MetadataEdit::StripAll => {
+                let streams = info.streams.iter().flat_map(|s| s.tags.iter());
+                for (key, value) in info.tags.iter().chain(streams) {
+                    if !TECHNICAL_TAGS.contains(&key.as_str()) {
+                        problems.push(format!("Tag {}={} was not removed.", key, value));
+                    }
+                }
+                for stream in info.streams_of_type("data") {
+                    problems.push(format!("Stream {} was not removed.", stream.describe()));
+                }
+            }
+        }
+        problems
+    }
+}
+
+pub fn print_tags(info: &MediaInfo) {
+    println!("Global tags:");
+    for (key, value) in info.tags.iter() {
+        println!("  {}: {}", key, value);
+    }
+    for stream in info.streams.iter() {
+        println!("Stream {}:", stream.describe());
+        for (key, value) in stream.tags.iter() {
+            println!("  {}: {}", key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::probe::StreamInfo;
+
+    #[test]
+    fn should_report_tags_left_after_stripping() {
+        let mut info = MediaInfo::default();
+        info.tags
+            .insert("major_brand".to_owned(), "isom".to_owned());
+        info.tags
+            .insert("location".to_owned(), "+52.2297+021.0122/".to_owned());
+        let mut stream = StreamInfo::default();
+        stream
+            .tags
+            .insert("handler_name".to_owned(), "VideoHandler".to_owned());
+        info.streams.push(stream);
+        let mut subtitle = StreamInfo {
+            codec_type: "subtitle".to_owned(),
+            ..Default::default()
+        };
+        subtitle
+            .tags
+            .insert("title".to_owned(), "Director's commentary".to_owned());
+        info.streams.push(subtitle);
+        info.streams.push(StreamInfo {
+            index: 3,
+            codec_type: "data".to_owned(),
+            codec_name: "bin_data".to_owned(),
+            ..Default::default()
+        });
+
+        let problems = MetadataEdit::StripAll.verify(&info);
+        assert_eq!(
+            problems,
+            vec![
+                "Tag location=+52.2297+021.0122/ was not removed.",
+                "Tag title=Director's commentary was not removed.",
+                "Stream #3 data bin_data was not removed."
+            ]
+        );
+    }
+
+    #[test]
+    fn should_verify_set_tags() {
+        let edit = MetadataEdit::Set {
+            tags: vec![("title".to_owned(), "Meeting".to_owned())],
+            language: None,
+        };
+        let mut info = MediaInfo::default();
+        assert_eq!(edit.verify(&info), vec!["Tag title is missing."]);
+
+        info.tags.insert("title".to_owned(), "Meeting".to_owned());
+        assert!(edit.verify(&info).is_empty());
+    }
+}
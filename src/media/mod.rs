@@ -1,3 +1,26 @@
+pub mod animation;
+pub mod audio_merge;
+pub mod audio_settings;
+pub mod channels;
+pub mod chapters;
 pub mod codecs;
+pub mod crop;
+pub mod cut_list;
+pub mod detection;
+pub mod frame_rate;
+pub mod interlace;
+pub mod metadata;
+pub mod orientation;
+pub mod playback;
+pub mod probe;
+pub mod scaling;
+pub mod segment;
+pub mod smart_cut;
+pub mod speed;
+pub mod stream_mapping;
+pub mod subtitles;
+pub mod text_overlay;
+pub mod thumbnail;
 pub mod transcoder;
 pub mod video_check;
+pub mod watermark;
@@ -0,0 +1,79 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transform {
+    /// Clockwise degrees, 90, 180 or 270.
+    Rotate(u16),
+    HorizontalFlip,
+    VerticalFlip,
+}
+
+impl Transform {
+    /// Filters applied after ffmpeg auto-rotated the input, so they act on the picture as displayed.
+    pub fn filters(&self) -> Vec<String> {
+        let filters: &[&str] = match self {
+            Transform::Rotate(90) => &["transpose=clock"],
+            Transform::Rotate(180) => &["hflip", "vflip"],
+            Transform::Rotate(270) => &["transpose=cclock"],
+            Transform::Rotate(_) => &[],
+            Transform::HorizontalFlip => &["hflip"],
+            Transform::VerticalFlip => &["vflip"],
+        };
+        filters.iter().map(|f| f.to_string()).collect()
+    }
+
+    /// The display matrix for this transform on top of the `current` clockwise rotation.
+    pub fn display_matrix(&self, current: u16) -> DisplayMatrix {
+        let mut matrix = DisplayMatrix {
+            rotation: current,
+            horizontal_flip: false,
+            vertical_flip: false,
+        };
+        match self {
+            Transform::Rotate(degrees) => matrix.rotation = (current + degrees) % 360,
+            Transform::HorizontalFlip => matrix.horizontal_flip = true,
+            Transform::VerticalFlip => matrix.vertical_flip = true,
+        }
+        matrix
+    }
+}
+
+/// Orientation stored as metadata only, the video stream is copied untouched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayMatrix {
+    /// Clockwise degrees.
+    pub rotation: u16,
+    pub horizontal_flip: bool,
+    pub vertical_flip: bool,
+}
+
+impl DisplayMatrix {
+    /// Input options, they replace the whole display matrix of the first video stream.
+    /// `-display_rotation` takes counter-clockwise degrees.
+    pub fn as_args(&self) -> String {
+        let mut args = format!(" -display_rotation:v:0 {}", (360 - self.rotation) % 360);
+        if self.horizontal_flip {
+            args.push_str(" -display_hflip:v:0");
+        }
+        if self.vertical_flip {
+            args.push_str(" -display_vflip:v:0");
+        }
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_combine_rotation_with_current_matrix() {
+        let matrix = Transform::Rotate(270).display_matrix(180);
+        assert_eq!(matrix.rotation, 90);
+        assert_eq!(matrix.as_args(), " -display_rotation:v:0 270");
+
+        let matrix = Transform::HorizontalFlip.display_matrix(90);
+        assert_eq!(
+            matrix.as_args(),
+            " -display_rotation:v:0 270 -display_hflip:v:0"
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use crate::media::detection::Interval;
+
+/// Default length of the chunks reversed one at a time. `reverse` keeps every frame of its
+/// input in memory, so chunking bounds memory use by the chunk length instead of the clip length.
+pub const DEFAULT_CHUNK_SECONDS: f64 = 10.0;
+/// Upper bound for the repetitions of a loop, a short or mis-probed clip would otherwise ask
+/// for millions of them.
+pub const MAX_LOOPS: u32 = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct ReverseOptions {
+    /// Video and audio are reversed chunk by chunk, `areverse` buffers its whole input just like
+    /// `reverse`. The audio chunks are kept as PCM and encoded once after joining, a lossy encoder
+    /// would add its delay at every chunk boundary.
+    pub chunks: Vec<Interval>,
+    /// One video-only file per chunk, in the same order as `chunks`.
+    pub parts: Vec<String>,
+    /// Concat list of the parts, last chunk first.
+    pub list_file: String,
+    /// One WAV file per chunk, empty for a source without audio.
+    pub audio_parts: Vec<String>,
+    /// Concat list of the audio parts, last chunk first.
+    pub audio_list_file: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoopOptions {
+    pub count: u32,
+    /// Concat list with `count` entries of the input. The concat demuxer stream copies every
+    /// repetition, without a list the input is re-encoded with `-stream_loop`.
+    pub list_file: Option<String>,
+    /// Cuts the result at a target duration.
+    pub duration: Option<f64>,
+}
+
+/// Consecutive ranges of at most `chunk` seconds covering the whole duration.
+pub fn chunks(duration: f64, chunk: f64) -> Vec<Interval> {
+    let count = (duration / chunk).ceil().max(1.0) as usize;
+    (0..count)
+        .map(|i| Interval {
+            start: i as f64 * chunk,
+            end: ((i + 1) as f64 * chunk).min(duration),
+        })
+        .collect()
+}
+
+/// Repetitions needed to reach the target duration, `None` for a clip without duration or
+/// when more than `MAX_LOOPS` would be needed.
+pub fn loop_count(duration: f64, target: f64) -> Option<u32> {
+    if duration <= 0.0 {
+        return None;
+    }
+    let count = (target / duration).ceil().max(1.0);
+    match count <= MAX_LOOPS as f64 {
+        true => Some(count as u32),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_split_into_chunks() {
+        let chunks = chunks(25.0, 10.0);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].start, 20.0);
+        assert_eq!(chunks[2].end, 25.0);
+        assert_eq!(super::chunks(4.0, 10.0).len(), 1);
+    }
+
+    #[test]
+    fn should_count_loops_for_target_duration() {
+        assert_eq!(loop_count(10.0, 35.0), Some(4));
+        assert_eq!(loop_count(10.0, 30.0), Some(3));
+        assert_eq!(loop_count(10.0, 5.0), Some(1));
+        assert_eq!(loop_count(0.0, 30.0), None);
+        assert_eq!(loop_count(0.001, 3600.0), None);
+    }
+}
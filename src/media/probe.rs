@@ -0,0 +1,306 @@
+use crate::command::command_runner;
+use crate::error::TranscoderError;
+use crate::media::chapters::Chapter;
+use crate::string_utils;
+use log::debug;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default)]
+pub struct StreamInfo {
+    pub index: usize,
+    pub codec_type: String,
+    pub codec_name: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub frame_rate: Option<f64>,
+    pub sample_aspect_ratio: Option<f64>,
+    pub pix_fmt: Option<String>,
+    /// `progressive`, `tt`, `bb`, `tb` or `bt`, missing when the container doesn't say.
+    pub field_order: Option<String>,
+    pub profile: Option<String>,
+    /// As reported by ffprobe, e.g. 40 for H.264 level 4.0 or 120 for HEVC level 4.
+    pub level: Option<i32>,
+    pub refs: Option<u32>,
+    pub bit_rate: Option<u64>,
+    pub time_base: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u8>,
+    pub channel_layout: Option<String>,
+    /// Display matrix rotation from the side data, counter-clockwise degrees.
+    pub rotation: Option<i32>,
+    pub tags: BTreeMap<String, String>,
+}
+
+impl StreamInfo {
+    pub fn language(&self) -> Option<&str> {
+        self.tags.get("language").map(|l| l.as_str())
+    }
+
+    /// Clockwise rotation applied when the stream is displayed, from the display matrix or
+    /// the legacy `rotate` tag.
+    pub fn display_rotation(&self) -> u16 {
+        let clockwise = match self.rotation {
+            Some(rotation) => -rotation,
+            None => self
+                .tags
+                .get("rotate")
+                .and_then(|r| r.parse().ok())
+                .unwrap_or(0),
+        };
+        clockwise.rem_euclid(360) as u16
+    }
+
+    pub fn describe(&self) -> String {
+        let mut description = format!("#{} {} {}", self.index, self.codec_type, self.codec_name);
+        if let (Some(w), Some(h)) = (self.width, self.height) {
+            description.push_str(format!(" {}x{}", w, h).as_str());
+        }
+        if let Some(language) = self.language() {
+            description.push_str(format!(" [{}]", language).as_str());
+        }
+        description
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MediaInfo {
+    pub format_name: String,
+    pub duration: Option<f64>,
+    pub size: Option<u64>,
+    pub bit_rate: Option<u64>,
+    pub streams: Vec<StreamInfo>,
+    pub tags: BTreeMap<String, String>,
+    pub chapters: Vec<Chapter>,
+}
+
+impl MediaInfo {
+    pub fn video_stream(&self) -> Option<&StreamInfo> {
+        self.streams.iter().find(|s| s.codec_type == "video")
+    }
+
+    pub fn streams_of_type<'a>(
+        &'a self,
+        codec_type: &'a str,
+    ) -> impl Iterator<Item = &'a StreamInfo> {
+        self.streams
+            .iter()
+            .filter(move |s| s.codec_type == codec_type)
+    }
+
+    /// The `timecode` tag of the container or of any stream (e.g. a `tmcd` data stream).
+    pub fn timecode(&self) -> Option<&str> {
+        std::iter::once(&self.tags)
+            .chain(self.streams.iter().map(|s| &s.tags))
+            .find_map(|tags| tags.get("timecode"))
+            .map(|tc| tc.as_str())
+    }
+}
+
+pub fn probe(path: &str) -> Result<MediaInfo, TranscoderError> {
+    let cmd = format!(
+        "ffprobe -v error -show_format -show_streams -show_chapters -of flat {}",
+        string_utils::wrap_with_quotes_if_whitespace(path)
+    );
+    let lines = command_runner::execute_cmd_get_lines(&cmd);
+    if lines.is_empty() {
+        return Err(TranscoderError::ProbeFailed(path.to_owned()));
+    }
+    let info = parse_flat_output(&lines);
+    debug!("Probed {}: {:?}", path, info);
+    Ok(info)
+}
+
+fn parse_flat_output(lines: &[String]) -> MediaInfo {
+    let mut info = MediaInfo::default();
+    for line in lines {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim_matches('"');
+        if let Some(key) = key.strip_prefix("format.") {
+            match key {
+                "format_name" => info.format_name = value.to_owned(),
+                "duration" => info.duration = value.parse().ok(),
+                "size" => info.size = value.parse().ok(),
+                "bit_rate" => info.bit_rate = value.parse().ok(),
+                _ => {
+                    if let Some(tag) = key.strip_prefix("tags.") {
+                        info.tags.insert(tag.to_owned(), value.to_owned());
+                    }
+                }
+            }
+        } else if let Some(key) = key.strip_prefix("chapters.chapter.") {
+            let Some((idx, key)) = key.split_once('.') else {
+                continue;
+            };
+            let Ok(idx) = idx.parse::<usize>() else {
+                continue;
+            };
+            while info.chapters.len() <= idx {
+                info.chapters.push(Chapter::default());
+            }
+            let chapter = &mut info.chapters[idx];
+            match key {
+                "start_time" => chapter.start = value.parse().unwrap_or_default(),
+                "end_time" => chapter.end = value.parse().unwrap_or_default(),
+                "tags.title" => chapter.title = value.to_owned(),
+                _ => {}
+            }
+        } else if let Some(key) = key.strip_prefix("streams.stream.") {
+            let Some((idx, key)) = key.split_once('.') else {
+                continue;
+            };
+            let Ok(idx) = idx.parse::<usize>() else {
+                continue;
+            };
+            while info.streams.len() <= idx {
+                info.streams.push(StreamInfo::default());
+            }
+            let stream = &mut info.streams[idx];
+            match key {
+                "index" => stream.index = value.parse().unwrap_or(idx),
+                "codec_type" => stream.codec_type = value.to_owned(),
+                "codec_name" => stream.codec_name = value.to_owned(),
+                "width" => stream.width = value.parse().ok(),
+                "height" => stream.height = value.parse().ok(),
+                // the average is the nominal rate, the real base rate of variable frame rate
+                // footage is often the maximum the timebase allows
+                "avg_frame_rate" => stream.frame_rate = parse_rational(value).or(stream.frame_rate),
+                "r_frame_rate" => stream.frame_rate = stream.frame_rate.or(parse_rational(value)),
+                "sample_aspect_ratio" => {
+                    stream.sample_aspect_ratio = parse_rational(&value.replace(':', "/"))
+                }
+                "pix_fmt" => stream.pix_fmt = Some(value.to_owned()),
+                "field_order" => stream.field_order = Some(value.to_owned()),
+                "profile" => stream.profile = Some(value.to_owned()),
+                "level" => stream.level = value.parse().ok().filter(|l| *l > 0),
+                "refs" => stream.refs = value.parse().ok(),
+                "bit_rate" => stream.bit_rate = value.parse().ok(),
+                "time_base" => stream.time_base = Some(value.to_owned()),
+                "sample_rate" => stream.sample_rate = value.parse().ok(),
+                "channels" => stream.channels = value.parse().ok(),
+                "channel_layout" => stream.channel_layout = Some(value.to_owned()),
+                _ => {
+                    if let Some(tag) = key.strip_prefix("tags.") {
+                        stream.tags.insert(tag.to_owned(), value.to_owned());
+                    } else if key.starts_with("side_data_list.") && key.ends_with(".rotation") {
+                        stream.rotation = value.parse::<f64>().ok().map(|r| r.round() as i32);
+                    }
+                }
+            }
+        }
+    }
+    info
+}
+
+/// Parses ffprobe rationals such as `30000/1001`.
+fn parse_rational(value: &str) -> Option<f64> {
+    let (num, den) = value.split_once('/')?;
+    let (num, den) = (num.parse::<f64>().ok()?, den.parse::<f64>().ok()?);
+    match den == 0.0 || num == 0.0 {
+        true => None,
+        false => Some(num / den),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &str) -> Vec<String> {
+        s.lines().map(|l| l.to_owned()).collect()
+    }
+
+    #[test]
+    fn should_parse_flat_probe_output() {
+        let output = lines(
+            r#"streams.stream.0.index=0
+streams.stream.0.codec_name="h264"
+streams.stream.0.codec_type="video"
+streams.stream.0.width=1920
+streams.stream.0.height=1080
+streams.stream.0.r_frame_rate="90000/1"
+streams.stream.0.avg_frame_rate="25/1"
+streams.stream.0.sample_aspect_ratio="64:45"
+streams.stream.0.field_order="tt"
+streams.stream.1.index=1
+streams.stream.1.codec_name="aac"
+streams.stream.1.codec_type="audio"
+streams.stream.1.sample_rate="48000"
+streams.stream.1.channels=6
+streams.stream.1.channel_layout="5.1(side)"
+streams.stream.1.tags.language="pol"
+chapters.chapter.0.id=0
+chapters.chapter.0.start_time="0.000000"
+chapters.chapter.0.end_time="60.500000"
+chapters.chapter.0.tags.title="Intro"
+format.format_name="mov,mp4,m4a,3gp,3g2,mj2"
+format.duration="125.500000"
+format.size="1048576"
+format.bit_rate="66841"
+format.tags.title="Meeting""#,
+        );
+        let info = parse_flat_output(&output);
+
+        assert_eq!(info.duration, Some(125.5));
+        assert_eq!(info.size, Some(1048576));
+        assert_eq!(info.bit_rate, Some(66841));
+        assert_eq!(info.tags.get("title").unwrap(), "Meeting");
+        assert_eq!(info.streams.len(), 2);
+        let video = info.video_stream().unwrap();
+        assert_eq!(video.codec_name, "h264");
+        assert_eq!(video.width, Some(1920));
+        assert_eq!(video.frame_rate, Some(25.0));
+        let fallback = parse_flat_output(&lines(
+            r#"streams.stream.0.r_frame_rate="30000/1001"
+streams.stream.0.avg_frame_rate="0/0""#,
+        ));
+        assert_eq!(fallback.streams[0].frame_rate, Some(30000.0 / 1001.0));
+        assert_eq!(video.sample_aspect_ratio, Some(64.0 / 45.0));
+        assert_eq!(video.field_order.as_deref(), Some("tt"));
+        assert_eq!(info.streams[1].codec_type, "audio");
+        assert_eq!(info.streams[1].language(), Some("pol"));
+        assert_eq!(info.streams[1].sample_rate, Some(48000));
+        assert_eq!(info.streams[1].channels, Some(6));
+        assert_eq!(info.streams[1].channel_layout.as_deref(), Some("5.1(side)"));
+        assert_eq!(info.streams_of_type("audio").count(), 1);
+        assert_eq!(
+            info.chapters,
+            vec![Chapter {
+                start: 0.0,
+                end: 60.5,
+                title: "Intro".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn should_parse_display_rotation() {
+        let output = lines(
+            r#"streams.stream.0.codec_type="video"
+streams.stream.0.side_data_list.side_data.0.side_data_type="Display Matrix"
+streams.stream.0.side_data_list.side_data.0.rotation=-90
+streams.stream.1.codec_type="video"
+streams.stream.1.tags.rotate="180""#,
+        );
+        let info = parse_flat_output(&output);
+
+        assert_eq!(info.streams[0].rotation, Some(-90));
+        assert_eq!(info.streams[0].display_rotation(), 90);
+        assert_eq!(info.streams[1].display_rotation(), 180);
+    }
+
+    #[test]
+    fn should_find_timecode_tag() {
+        let output = lines(
+            r#"streams.stream.0.codec_type="video"
+streams.stream.1.codec_type="data"
+streams.stream.1.codec_name="tmcd"
+streams.stream.1.tags.timecode="10:00:00;00""#,
+        );
+        let info = parse_flat_output(&output);
+
+        assert_eq!(info.timecode(), Some("10:00:00;00"));
+        assert_eq!(MediaInfo::default().timecode(), None);
+    }
+}
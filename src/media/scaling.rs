@@ -0,0 +1,197 @@
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ScaleAlgorithm {
+    #[default]
+    Lanczos,
+    Bicubic,
+}
+
+impl ScaleAlgorithm {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ScaleAlgorithm::Lanczos => "lanczos",
+            ScaleAlgorithm::Bicubic => "bicubic",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<ScaleAlgorithm> {
+        match value.to_lowercase().as_str() {
+            "lanczos" => Some(ScaleAlgorithm::Lanczos),
+            "bicubic" => Some(ScaleAlgorithm::Bicubic),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Padding {
+    Color(String),
+    /// A blurred, zoomed copy of the picture fills the bars.
+    Blur,
+}
+
+impl Padding {
+    pub fn parse(value: &str) -> Option<Padding> {
+        match value.to_lowercase().as_str() {
+            "blur" => Some(Padding::Blur),
+            c if !c.is_empty() && c.chars().all(|c| c.is_ascii_alphanumeric() || c == '#') => {
+                Some(Padding::Color(c.to_owned()))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScalePolicy {
+    /// The box is given for landscape video and turned for vertical video.
+    pub box_width: u32,
+    pub box_height: u32,
+    pub upscale: bool,
+    pub algorithm: ScaleAlgorithm,
+    pub padding: Option<Padding>,
+}
+
+impl Default for ScalePolicy {
+    fn default() -> Self {
+        ScalePolicy {
+            box_width: 1280,
+            box_height: 720,
+            upscale: false,
+            algorithm: ScaleAlgorithm::default(),
+            padding: None,
+        }
+    }
+}
+
+/// Probed size of the source. `rotation` is the clockwise display rotation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceSize {
+    pub width: u32,
+    pub height: u32,
+    pub sample_aspect_ratio: f64,
+    pub rotation: u16,
+}
+
+impl SourceSize {
+    /// Size as displayed, with square pixels and the rotation applied.
+    pub fn display_size(&self) -> (f64, f64) {
+        let width = self.width as f64 * self.sample_aspect_ratio;
+        let height = self.height as f64;
+        match self.rotation {
+            90 | 270 => (height, width),
+            _ => (width, height),
+        }
+    }
+}
+
+/// A policy resolved against the source it is applied to.
+#[derive(Debug, Clone)]
+pub struct Scaling {
+    pub policy: ScalePolicy,
+    pub source: SourceSize,
+}
+
+impl Scaling {
+    pub fn target_box(&self) -> (u32, u32) {
+        let (width, height) = self.source.display_size();
+        let (box_width, box_height) = (self.policy.box_width, self.policy.box_height);
+        match (height > width) == (box_height > box_width) {
+            true => (even(box_width as f64), even(box_height as f64)),
+            false => (even(box_height as f64), even(box_width as f64)),
+        }
+    }
+
+    /// Largest even size with the display aspect ratio that fits the box.
+    pub fn output_size(&self) -> (u32, u32) {
+        let (width, height) = self.source.display_size();
+        let (box_width, box_height) = self.target_box();
+        let mut factor = (box_width as f64 / width).min(box_height as f64 / height);
+        if !self.policy.upscale {
+            factor = factor.min(1.0);
+        }
+        (even(width * factor), even(height * factor))
+    }
+
+    /// ffmpeg rotates the input before filtering, so the explicit output size
+    /// already has the right orientation. `setsar=1` marks the result as square pixels.
+    pub fn filters(&self) -> Vec<String> {
+        let (width, height) = self.output_size();
+        let (box_width, box_height) = self.target_box();
+        let flags = self.policy.algorithm.as_str();
+        let scale = format!("scale={}:{}:flags={}", width, height, flags);
+        let filters = match &self.policy.padding {
+            None => vec![scale],
+            Some(Padding::Color(color)) => vec![
+                scale,
+                format!(
+                    "pad={}:{}:(ow-iw)/2:(oh-ih)/2:color={}",
+                    box_width, box_height, color
+                ),
+            ],
+            Some(Padding::Blur) => vec![format!(
+                "split[bg][fg];[bg]scale={bw}:{bh}:force_original_aspect_ratio=increase:flags={flags},crop={bw}:{bh},boxblur=20:5[bg];[fg]{scale}[fg];[bg][fg]overlay=(W-w)/2:(H-h)/2",
+                bw = box_width,
+                bh = box_height
+            )],
+        };
+        filters
+            .into_iter()
+            .chain(std::iter::once("setsar=1".to_owned()))
+            .collect()
+    }
+}
+
+fn even(value: f64) -> u32 {
+    ((value / 2.0).floor() as u32 * 2).max(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scaling(width: u32, height: u32, sar: f64, rotation: u16) -> Scaling {
+        Scaling {
+            policy: ScalePolicy::default(),
+            source: SourceSize {
+                width,
+                height,
+                sample_aspect_ratio: sar,
+                rotation,
+            },
+        }
+    }
+
+    #[test]
+    fn should_fit_in_box_without_upscaling() {
+        assert_eq!(scaling(1920, 1080, 1.0, 0).output_size(), (1280, 720));
+        assert_eq!(scaling(1920, 800, 1.0, 0).output_size(), (1280, 532));
+        assert_eq!(scaling(640, 360, 1.0, 0).output_size(), (640, 360));
+    }
+
+    #[test]
+    fn should_turn_box_for_vertical_video() {
+        assert_eq!(scaling(1080, 1920, 1.0, 0).output_size(), (720, 1280));
+        // stored landscape, displayed portrait
+        assert_eq!(scaling(1920, 1080, 1.0, 90).output_size(), (720, 1280));
+    }
+
+    #[test]
+    fn should_use_display_aspect_ratio() {
+        // anamorphic DVD, 720x576 with 64:45 pixels displays as 1024x576
+        assert_eq!(scaling(720, 576, 64.0 / 45.0, 0).output_size(), (1024, 576));
+    }
+
+    #[test]
+    fn should_build_padding_filters() {
+        let mut scaling = scaling(640, 480, 1.0, 0);
+        scaling.policy.padding = Some(Padding::Color("black".to_owned()));
+        assert_eq!(
+            scaling.filters(),
+            vec![
+                "scale=640:480:flags=lanczos",
+                "pad=1280:720:(ow-iw)/2:(oh-ih)/2:color=black",
+                "setsar=1"
+            ]
+        );
+    }
+}
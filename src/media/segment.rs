@@ -0,0 +1,48 @@
+/// Part of the size limit left as a margin, since cuts happen at the next keyframe and bitrate varies.
+const SIZE_MARGIN: f64 = 0.9;
+
+#[derive(Debug, Clone)]
+pub enum SegmentOptions {
+    /// Pieces of a fixed length, stream copied so cuts happen at the next keyframe.
+    Every(f64),
+    /// Pieces split at the given times, re-encoded with keyframes forced at the split points.
+    At(Vec<f64>),
+}
+
+impl SegmentOptions {
+    pub fn as_args(&self) -> String {
+        match self {
+            SegmentOptions::Every(segment_time) => format!(
+                " -map 0 -c copy -f segment -segment_time {} -segment_start_number 1 -reset_timestamps 1",
+                segment_time
+            ),
+            SegmentOptions::At(times) => {
+                let times = times
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",");
+                format!(
+                    " -map 0:v:0 -map 0:a? -force_key_frames {times} -f segment -segment_times {times} -segment_start_number 1 -reset_timestamps 1"
+                )
+            }
+        }
+    }
+}
+
+/// Calculates how many seconds of a stream with the given bitrate fit into `max_megabytes`.
+pub fn segment_time_for_size(max_megabytes: f64, bit_rate: u64) -> f64 {
+    let bits = max_megabytes * 1_048_576.0 * 8.0 * SIZE_MARGIN;
+    (bits / bit_rate as f64).floor()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_calculate_segment_time_for_size() {
+        // 8 Mbit/s -> 1 MiB per second
+        assert_eq!(segment_time_for_size(100.0, 8_388_608), 90.0);
+    }
+}
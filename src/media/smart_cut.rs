@@ -0,0 +1,302 @@
+use crate::command::command_runner;
+use crate::media::probe::MediaInfo;
+use crate::string_utils;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CutPart {
+    pub start: f64,
+    pub end: f64,
+    /// Copied parts span whole GOPs, the boundary ones are re-encoded.
+    pub copy: bool,
+    pub file: String,
+}
+
+/// Encoder settings matching the source, so re-encoded GOPs can be joined with the copied ones.
+/// H.264 and HEVC parts are written as MPEG-TS with the parameter sets repeated in-band, so a
+/// decoder picks up the re-encoded SPS/PPS at every cut point after the final remux.
+#[derive(Debug, Clone, Default)]
+pub struct EncodeParams {
+    /// Codec of the source video stream, e.g. `h264`.
+    pub video_codec: String,
+    pub video_encoder: String,
+    pub profile: Option<String>,
+    pub level: Option<String>,
+    pub refs: Option<u32>,
+    /// Source video bitrate, the re-encoded parts fall back to a high quality CRF without it.
+    pub bit_rate: Option<u64>,
+    pub pix_fmt: Option<String>,
+    pub timescale: Option<String>,
+    pub audio_encoder: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u8>,
+}
+
+impl EncodeParams {
+    pub fn from_probe(info: &MediaInfo) -> EncodeParams {
+        let mut params = EncodeParams::default();
+        if let Some(video) = info.video_stream() {
+            params.video_codec = video.codec_name.clone();
+            params.video_encoder = encoder_for(&video.codec_name).to_owned();
+            params.profile = video
+                .profile
+                .as_deref()
+                .and_then(|p| encoder_profile(&video.codec_name, p));
+            params.level = video
+                .level
+                .and_then(|l| encoder_level(&video.codec_name, l));
+            params.refs = video.refs;
+            params.bit_rate = video.bit_rate;
+            params.pix_fmt = video.pix_fmt.clone();
+            params.timescale = video
+                .time_base
+                .as_ref()
+                .and_then(|tb| tb.split_once('/'))
+                .map(|(_, den)| den.to_owned());
+        }
+        if let Some(audio) = info.streams_of_type("audio").next() {
+            params.audio_encoder = Some(encoder_for(&audio.codec_name).to_owned());
+            params.sample_rate = audio.sample_rate;
+            params.channels = audio.channels;
+        }
+        params
+    }
+
+    /// Bitstream filter that moves the parameter sets in-band, for the codecs that have one.
+    pub fn annexb_filter(&self) -> Option<&str> {
+        match self.video_codec.as_str() {
+            "h264" => Some("h264_mp4toannexb"),
+            "hevc" => Some("hevc_mp4toannexb"),
+            _ => None,
+        }
+    }
+
+    /// Extension of the part files, MPEG-TS whenever the parameter sets can go in-band.
+    pub fn part_extension<'a>(&self, source_ext: &'a str) -> &'a str {
+        match self.annexb_filter() {
+            Some(_) => ".ts",
+            None => source_ext,
+        }
+    }
+
+    /// Arguments of the re-encoded parts.
+    pub fn as_args(&self) -> String {
+        let mut args = format!(" -c:v {}", self.video_encoder);
+        if let Some(profile) = &self.profile {
+            args.push_str(format!(" -profile:v {}", profile).as_str());
+        }
+        if let Some(refs) = self.refs {
+            args.push_str(format!(" -refs {}", refs).as_str());
+        }
+        let x26x = matches!(self.video_encoder.as_str(), "libx264" | "libx265");
+        match self.bit_rate {
+            Some(bit_rate) => args.push_str(format!(" -b:v {}", bit_rate).as_str()),
+            None if x26x => args.push_str(" -crf 18"),
+            None => {}
+        }
+        match self.video_encoder.as_str() {
+            "libx264" => {
+                if let Some(level) = &self.level {
+                    args.push_str(format!(" -level {}", level).as_str());
+                }
+                args.push_str(" -x264-params repeat-headers=1");
+            }
+            "libx265" => {
+                let level = self
+                    .level
+                    .as_ref()
+                    .map(|l| format!("level-idc={}:", l))
+                    .unwrap_or_default();
+                args.push_str(format!(" -x265-params {}repeat-headers=1", level).as_str());
+            }
+            _ => {}
+        }
+        if let Some(pix_fmt) = &self.pix_fmt {
+            args.push_str(format!(" -pix_fmt {}", pix_fmt).as_str());
+        }
+        if let Some(encoder) = &self.audio_encoder {
+            args.push_str(format!(" -c:a {}", encoder).as_str());
+        }
+        if let Some(sample_rate) = self.sample_rate {
+            args.push_str(format!(" -ar {}", sample_rate).as_str());
+        }
+        if let Some(channels) = self.channels {
+            args.push_str(format!(" -ac {}", channels).as_str());
+        }
+        args
+    }
+
+    /// Arguments of the stream copied parts.
+    pub fn copy_args(&self) -> String {
+        match self.annexb_filter() {
+            Some(filter) => format!(" -c copy -bsf:v {}", filter),
+            None => " -c copy".to_owned(),
+        }
+    }
+
+    /// Arguments of the final remux of all parts into the output container.
+    pub fn remux_args(&self) -> String {
+        match &self.timescale {
+            Some(timescale) => format!(" -video_track_timescale {}", timescale),
+            None => String::new(),
+        }
+    }
+}
+
+/// Maps an ffprobe profile name to the encoder's `-profile:v` value.
+fn encoder_profile(codec_name: &str, profile: &str) -> Option<String> {
+    let profile = match (codec_name, profile.to_lowercase().as_str()) {
+        ("h264", "constrained baseline" | "baseline") => "baseline",
+        ("h264", "main") => "main",
+        ("h264", "high") => "high",
+        ("h264", "high 10") => "high10",
+        ("h264", "high 4:2:2") => "high422",
+        ("h264", "high 4:4:4 predictive") => "high444",
+        ("hevc", "main") => "main",
+        ("hevc", "main 10") => "main10",
+        _ => return None,
+    };
+    Some(profile.to_owned())
+}
+
+/// ffprobe reports H.264 levels times 10 and HEVC levels times 30.
+fn encoder_level(codec_name: &str, level: i32) -> Option<String> {
+    match codec_name {
+        "h264" => Some(format!("{}.{}", level / 10, level % 10)),
+        "hevc" => Some(format!("{}", (level as f64 / 3.0).round() / 10.0)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SmartCutOptions {
+    pub parts: Vec<CutPart>,
+    pub params: EncodeParams,
+    pub list_file: String,
+}
+
+fn encoder_for(codec_name: &str) -> &str {
+    match codec_name {
+        "h264" => "libx264",
+        "hevc" => "libx265",
+        "vp9" => "libvpx-vp9",
+        "vp8" => "libvpx",
+        "av1" => "libaom-av1",
+        "mp3" => "libmp3lame",
+        "opus" => "libopus",
+        "vorbis" => "libvorbis",
+        codec => codec,
+    }
+}
+
+/// Splits `start..end` into a re-encoded head up to the first keyframe, a stream copied middle
+/// up to the last keyframe and a re-encoded tail. A copied range must end on a keyframe too, or
+/// the demuxer cuts inside a GOP and the frames after `end` leak into the output.
+/// Returns `(start, end, copy)` ranges.
+pub fn plan(start: f64, end: f64, keyframes: &[f64]) -> Vec<(f64, f64, bool)> {
+    let inside = keyframes
+        .iter()
+        .copied()
+        .filter(|k| *k >= start && *k < end)
+        .collect::<Vec<_>>();
+    // a keyframe exactly at `end` lets the last GOP be copied whole
+    let copy_end = match keyframes.contains(&end) {
+        true => Some(end),
+        false => inside.last().copied(),
+    };
+    match (inside.first().copied(), copy_end) {
+        (Some(first), Some(copy_end)) if first < copy_end => {
+            let mut ranges = Vec::new();
+            if start < first {
+                ranges.push((start, first, false));
+            }
+            ranges.push((first, copy_end, true));
+            if copy_end < end {
+                ranges.push((copy_end, end, false));
+            }
+            ranges
+        }
+        _ => vec![(start, end, false)],
+    }
+}
+
+pub fn probe_keyframes(path: &str, start: f64, end: f64) -> Vec<f64> {
+    let cmd = format!(
+        "ffprobe -v error -select_streams v:0 -read_intervals {}%{} -show_entries packet=pts_time,flags -of csv=print_section=0 {}",
+        start,
+        end,
+        string_utils::wrap_with_quotes_if_whitespace(path)
+    );
+    parse_keyframes(&command_runner::execute_cmd_get_lines(&cmd))
+}
+
+/// Parses `pts_time,flags` packet lines, keeping the packets flagged as keyframes.
+fn parse_keyframes(lines: &[String]) -> Vec<f64> {
+    let mut keyframes = lines
+        .iter()
+        .filter_map(|line| line.split_once(','))
+        .filter(|(_, flags)| flags.starts_with('K'))
+        .filter_map(|(pts, _)| pts.parse::<f64>().ok())
+        .collect::<Vec<_>>();
+    keyframes.sort_by(|a, b| a.total_cmp(b));
+    keyframes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::probe::StreamInfo;
+
+    #[test]
+    fn should_parse_keyframes_from_packets() {
+        let lines = ["4.000000,K__", "4.040000,___", "2.000000,K_", "N/A,K__"]
+            .iter()
+            .map(|l| l.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(parse_keyframes(&lines), vec![2.0, 4.0]);
+    }
+
+    #[test]
+    fn should_plan_smart_cut() {
+        let keyframes = [0.0, 2.0, 4.0, 6.0, 8.0];
+        assert_eq!(
+            plan(1.5, 7.0, &keyframes),
+            vec![(1.5, 2.0, false), (2.0, 6.0, true), (6.0, 7.0, false)]
+        );
+        assert_eq!(plan(2.0, 6.0, &keyframes), vec![(2.0, 6.0, true)]);
+        assert_eq!(
+            plan(2.0, 7.0, &keyframes),
+            vec![(2.0, 6.0, true), (6.0, 7.0, false)]
+        );
+        assert_eq!(plan(2.5, 4.5, &keyframes), vec![(2.5, 4.5, false)]);
+        assert_eq!(plan(2.5, 3.5, &keyframes), vec![(2.5, 3.5, false)]);
+    }
+
+    #[test]
+    fn should_match_source_encoding() {
+        let info = MediaInfo {
+            streams: vec![StreamInfo {
+                codec_type: "video".to_owned(),
+                codec_name: "h264".to_owned(),
+                profile: Some("High".to_owned()),
+                level: Some(41),
+                refs: Some(4),
+                bit_rate: Some(8_000_000),
+                pix_fmt: Some("yuv420p".to_owned()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let params = EncodeParams::from_probe(&info);
+        assert_eq!(
+            params.as_args(),
+            " -c:v libx264 -profile:v high -refs 4 -b:v 8000000 -level 4.1 -x264-params repeat-headers=1 -pix_fmt yuv420p"
+        );
+        assert_eq!(params.copy_args(), " -c copy -bsf:v h264_mp4toannexb");
+        assert_eq!(params.part_extension(".mp4"), ".ts");
+        assert_eq!(encoder_level("hevc", 123).as_deref(), Some("4.1"));
+        assert_eq!(
+            encoder_profile("h264", "Constrained Baseline").as_deref(),
+            Some("baseline")
+        );
+    }
+}
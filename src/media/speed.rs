@@ -0,0 +1,99 @@
+pub const MIN_FACTOR: f64 = 0.25;
+pub const MAX_FACTOR: f64 = 8.0;
+/// Range of a single `atempo` instance.
+const ATEMPO_MIN: f64 = 0.5;
+const ATEMPO_MAX: f64 = 2.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpeedChange {
+    /// Plays `factor` times faster, e.g. 2 for double and 0.5 for half speed.
+    Factor { factor: f64, keep_audio: bool },
+    /// Keeps one frame every `interval` seconds and plays them at `frame_rate`.
+    Timelapse { interval: f64, frame_rate: u32 },
+}
+
+impl SpeedChange {
+    pub fn video_filters(&self) -> Vec<String> {
+        match self {
+            SpeedChange::Factor { factor, .. } => vec![format!("setpts=PTS/{}", factor)],
+            SpeedChange::Timelapse {
+                interval,
+                frame_rate,
+            } => vec![
+                format!("fps=1/{}", interval),
+                format!("setpts=N/{}/TB", frame_rate),
+            ],
+        }
+    }
+
+    pub fn args(&self) -> String {
+        match self {
+            SpeedChange::Factor {
+                factor,
+                keep_audio: true,
+            } => {
+                let chain = atempo_chain(*factor)
+                    .iter()
+                    .map(|tempo| format!("atempo={}", tempo))
+                    .collect::<Vec<_>>();
+                format!(r#" -af "{}""#, chain.join(","))
+            }
+            SpeedChange::Factor { .. } => " -an".to_owned(),
+            SpeedChange::Timelapse { frame_rate, .. } => format!(" -r {} -an", frame_rate),
+        }
+    }
+}
+
+/// Accepts `2`, `2x` or `0.5x` within the supported range.
+pub fn parse_factor(value: &str) -> Option<f64> {
+    let factor = value
+        .trim()
+        .trim_end_matches(['x', 'X'])
+        .parse::<f64>()
+        .ok()?;
+    match (MIN_FACTOR..=MAX_FACTOR).contains(&factor) {
+        true => Some(factor),
+        false => None,
+    }
+}
+
+/// Splits the factor into `atempo` steps within 0.5 - 2.0 whose product is the factor.
+pub fn atempo_chain(factor: f64) -> Vec<f64> {
+    let mut chain = Vec::new();
+    let mut rest = factor;
+    while rest > ATEMPO_MAX {
+        chain.push(ATEMPO_MAX);
+        rest /= ATEMPO_MAX;
+    }
+    while rest < ATEMPO_MIN {
+        chain.push(ATEMPO_MIN);
+        rest /= ATEMPO_MIN;
+    }
+    let rest = (rest * 1_000_000.0).round() / 1_000_000.0;
+    if rest != 1.0 || chain.is_empty() {
+        chain.push(rest);
+    }
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_chain_atempo_filters() {
+        assert_eq!(atempo_chain(1.5), vec![1.5]);
+        assert_eq!(atempo_chain(8.0), vec![2.0, 2.0, 2.0]);
+        assert_eq!(atempo_chain(3.0), vec![2.0, 1.5]);
+        assert_eq!(atempo_chain(0.25), vec![0.5, 0.5]);
+        assert_eq!(atempo_chain(0.3), vec![0.5, 0.6]);
+    }
+
+    #[test]
+    fn should_parse_factor() {
+        assert_eq!(parse_factor("2x"), Some(2.0));
+        assert_eq!(parse_factor("0.25"), Some(0.25));
+        assert_eq!(parse_factor("10"), None);
+        assert_eq!(parse_factor("fast"), None);
+    }
+}
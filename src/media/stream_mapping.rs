@@ -0,0 +1,104 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamSelection {
+    All,
+    Languages(Vec<String>),
+    Indices(Vec<usize>),
+}
+
+impl StreamSelection {
+    /// Accepts `all`, a list of stream indices (e.g. `0,1,3`) or language codes (e.g. `eng,pol`).
+    pub fn parse(s: &str) -> Option<StreamSelection> {
+        let s = s.trim().to_lowercase();
+        if s.is_empty() {
+            return None;
+        }
+        if s == "all" {
+            return Some(StreamSelection::All);
+        }
+        let items = s
+            .split(',')
+            .map(|i| i.trim())
+            .filter(|i| !i.is_empty())
+            .collect::<Vec<_>>();
+        let indices = items
+            .iter()
+            .map(|i| i.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>();
+        match indices {
+            Ok(indices) => Some(StreamSelection::Indices(indices)),
+            Err(_) => {
+                let valid = items
+                    .iter()
+                    .all(|i| i.chars().all(|c| c.is_ascii_alphabetic()));
+                match valid {
+                    true => Some(StreamSelection::Languages(
+                        items.iter().map(|i| i.to_string()).collect(),
+                    )),
+                    false => None,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StreamMapping {
+    pub selection: StreamSelection,
+    pub drop_subtitles: bool,
+    pub drop_data: bool,
+}
+
+impl StreamMapping {
+    pub fn as_args(&self) -> String {
+        let mut args = String::new();
+        match &self.selection {
+            StreamSelection::All => args.push_str(" -map 0"),
+            StreamSelection::Languages(languages) => {
+                args.push_str(" -map 0:v?");
+                for language in languages {
+                    args.push_str(format!(" -map 0:a:m:language:{}?", language).as_str());
+                }
+                if !self.drop_subtitles {
+                    for language in languages {
+                        args.push_str(format!(" -map 0:s:m:language:{}?", language).as_str());
+                    }
+                }
+            }
+            StreamSelection::Indices(indices) => {
+                for index in indices {
+                    args.push_str(format!(" -map 0:{}", index).as_str());
+                }
+            }
+        }
+        if self.drop_subtitles {
+            args.push_str(" -map -0:s");
+        }
+        if self.drop_data {
+            args.push_str(" -map -0:d");
+        }
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_stream_selection() {
+        assert_eq!(StreamSelection::parse("all"), Some(StreamSelection::All));
+        assert_eq!(
+            StreamSelection::parse("0, 1,3"),
+            Some(StreamSelection::Indices(vec![0, 1, 3]))
+        );
+        assert_eq!(
+            StreamSelection::parse("eng,POL"),
+            Some(StreamSelection::Languages(vec![
+                "eng".to_owned(),
+                "pol".to_owned()
+            ]))
+        );
+        assert_eq!(StreamSelection::parse("eng,1:2"), None);
+        assert_eq!(StreamSelection::parse(""), None);
+    }
+}
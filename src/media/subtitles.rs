@@ -0,0 +1,110 @@
+use crate::string_utils;
+
+const BITMAP_SUBTITLE_CODECS: [&str; 3] = ["hdmv_pgs_subtitle", "dvd_subtitle", "dvb_subtitle"];
+
+#[derive(Debug, Clone, Default)]
+pub struct SubtitleStyle {
+    pub font_name: Option<String>,
+    pub font_size: Option<u16>,
+    pub primary_colour: Option<String>,
+    pub outline: Option<u8>,
+}
+
+impl SubtitleStyle {
+    pub fn is_empty(&self) -> bool {
+        self.font_name.is_none()
+            && self.font_size.is_none()
+            && self.primary_colour.is_none()
+            && self.outline.is_none()
+    }
+
+    pub fn as_force_style(&self) -> String {
+        let mut style = Vec::new();
+        if let Some(v) = &self.font_name {
+            style.push(format!("FontName={}", v));
+        }
+        if let Some(v) = &self.font_size {
+            style.push(format!("FontSize={}", v));
+        }
+        if let Some(v) = &self.primary_colour {
+            style.push(format!("PrimaryColour={}", v));
+        }
+        if let Some(v) = &self.outline {
+            style.push(format!("Outline={}", v));
+        }
+        style.join(",")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SubtitleTrack {
+    pub file: String,
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExtractedSubtitle {
+    pub stream_index: usize,
+    pub output_file: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum SubtitleOperation {
+    BurnIn {
+        subtitle_file: String,
+        style: SubtitleStyle,
+    },
+    Mux {
+        tracks: Vec<SubtitleTrack>,
+        existing_subtitles: usize,
+    },
+    Extract {
+        streams: Vec<ExtractedSubtitle>,
+    },
+}
+
+pub fn burn_in_filter(subtitle_file: &str, style: &SubtitleStyle) -> String {
+    let path = string_utils::escape_filter_value(subtitle_file);
+    if is_ass(subtitle_file) {
+        return format!("ass={}", path);
+    }
+    let mut filter = format!("subtitles={}", path);
+    if !style.is_empty() {
+        // the style values are typed by the user, so the whole list is escaped like the path
+        let force_style = string_utils::escape_filter_value(&style.as_force_style());
+        filter.push_str(format!(":force_style={}", force_style).as_str());
+    }
+    filter
+}
+
+pub fn is_ass(path: &str) -> bool {
+    let path = path.to_lowercase();
+    path.ends_with(".ass") || path.ends_with(".ssa")
+}
+
+pub fn is_bitmap_codec(codec_name: &str) -> bool {
+    BITMAP_SUBTITLE_CODECS.contains(&codec_name)
+}
+
+/// Picks the subtitle codec supported by the output container for the given subtitle file.
+pub fn mux_codec(output: &str, subtitle_file: &str) -> Option<&'static str> {
+    let ext = string_utils::find_file_extension(output)
+        .ok()?
+        .to_lowercase();
+    match ext.as_str() {
+        ".mp4" | ".m4v" | ".mov" => Some("mov_text"),
+        ".mkv" => match is_ass(subtitle_file) {
+            true => Some("ass"),
+            false => Some("srt"),
+        },
+        ".webm" => Some("webvtt"),
+        _ => None,
+    }
+}
+
+pub fn extract_codec(output: &str) -> &'static str {
+    match output.to_lowercase().ends_with(".vtt") {
+        true => "webvtt",
+        false => "srt",
+    }
+}
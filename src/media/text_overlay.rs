@@ -0,0 +1,180 @@
+use crate::media::watermark::Position;
+use crate::string_utils::{self, Timecode};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverlayContent {
+    /// SMPTE timecode counting up from `start` (`HH:MM:SS:FF`, `;` before the frames for
+    /// drop frame) at the video frame `rate`.
+    Timecode { start: String, rate: String },
+    /// Text with drawtext expansions kept, e.g. `%{frame_num}`, `%{pts:hms}` or
+    /// `%{localtime:%Y-%m-%d}`.
+    Text(String),
+    /// Text shown as is, e.g. a file name.
+    Literal(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextStyle {
+    pub font_file: Option<String>,
+    pub size: u32,
+    pub color: String,
+    /// Draws a translucent box behind the text.
+    pub background: bool,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle {
+            font_file: None,
+            size: 32,
+            color: "white".to_owned(),
+            background: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextOverlay {
+    pub content: OverlayContent,
+    pub position: Position,
+    pub margin: u32,
+    pub style: TextStyle,
+}
+
+impl TextOverlay {
+    /// A `drawtext` filter, escaped for the double quoted `-vf` argument.
+    pub fn filter(&self) -> String {
+        let mut filter = String::from("drawtext=");
+        if let Some(font) = &self.style.font_file {
+            filter.push_str(&format!(
+                "fontfile={}:",
+                string_utils::escape_filter_value(font)
+            ));
+        }
+        match &self.content {
+            OverlayContent::Timecode { start, rate } => filter.push_str(&format!(
+                "timecode={}:rate={}",
+                string_utils::escape_filter_value(start),
+                rate
+            )),
+            OverlayContent::Text(text) => filter.push_str(&format!(
+                "text={}",
+                string_utils::escape_filter_value(&escape_expansion(text))
+            )),
+            OverlayContent::Literal(text) => filter.push_str(&format!(
+                "text={}:expansion=none",
+                string_utils::escape_filter_value(text)
+            )),
+        }
+        let (x, y) = self.position.coordinates(self.margin, "tw", "th");
+        filter.push_str(&format!(
+            ":fontsize={}:fontcolor={}:x={}:y={}",
+            self.style.size, self.style.color, x, y
+        ));
+        if self.style.background {
+            filter.push_str(":box=1:boxcolor=black@0.5:boxborderw=8");
+        }
+        filter
+    }
+}
+
+/// Frame rates drawtext supports drop frame timecode for.
+const DROP_FRAME_RATES: [f64; 2] = [30000.0 / 1001.0, 60000.0 / 1001.0];
+
+/// Accepts `HH:MM:SS:FF`, with `;` or `.` before the frames for drop frame timecode at
+/// 29.97 or 59.94 fps.
+pub fn parse_timecode(value: &str, rate: f64) -> Option<String> {
+    let timecode = Timecode::parse(value)?;
+    let drop_frame_rate = DROP_FRAME_RATES.iter().any(|r| (r - rate).abs() < 0.01);
+    (!timecode.drop_frame || drop_frame_rate).then(|| value.trim().to_owned())
+}
+
+/// drawtext expands `%{...}` and treats a backslash as an escape, so a lone `%` or `\` in the
+/// text is escaped while the expansions are kept as typed.
+fn escape_expansion(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' if chars.peek() == Some(&'{') => {
+                escaped.push(c);
+                for c in chars.by_ref() {
+                    escaped.push(c);
+                    if c == '}' {
+                        break;
+                    }
+                }
+            }
+            '%' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overlay(content: OverlayContent) -> TextOverlay {
+        TextOverlay {
+            content,
+            position: Position::TopLeft,
+            margin: 10,
+            style: TextStyle {
+                background: false,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn should_keep_expansions_and_escape_the_rest() {
+        assert_eq!(
+            escape_expansion(r"100% \ frame %{frame_num}"),
+            r"100\% \\ frame %{frame_num}"
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn should_build_drawtext_filters() {
+        let timecode = overlay(OverlayContent::Timecode {
+            start: "10:00:00;00".to_owned(),
+            rate: "30000/1001".to_owned(),
+        });
+        assert_eq!(
+            timecode.filter(),
+            r"drawtext=timecode=10\\\\:00\\\\:00\\;00:rate=30000/1001:fontsize=32:fontcolor=white:x=10:y=10"
+        );
+        let frame = overlay(OverlayContent::Text("DRAFT %{frame_num}".to_owned()));
+        assert_eq!(
+            frame.filter(),
+            "drawtext=text=DRAFT %{frame_num}:fontsize=32:fontcolor=white:x=10:y=10"
+        );
+        let name = overlay(OverlayContent::Literal("50%.mp4".to_owned()));
+        assert_eq!(
+            name.filter(),
+            "drawtext=text=50%.mp4:expansion=none:fontsize=32:fontcolor=white:x=10:y=10"
+        );
+    }
+
+    #[test]
+    fn should_parse_timecode() {
+        assert_eq!(
+            parse_timecode("01:00:00:00", 25.0).as_deref(),
+            Some("01:00:00:00")
+        );
+        assert_eq!(
+            parse_timecode("01:00:00;12", 30000.0 / 1001.0).as_deref(),
+            Some("01:00:00;12")
+        );
+        assert_eq!(parse_timecode("01:00:00;12", 25.0), None);
+        assert_eq!(parse_timecode("01:00:00.12", 30.0), None);
+        assert_eq!(parse_timecode("1:00:00:00", 25.0), None);
+        assert_eq!(parse_timecode("01:00:00", 25.0), None);
+    }
+}
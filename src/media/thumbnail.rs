@@ -0,0 +1,150 @@
+use crate::string_utils;
+
+#[derive(Debug, Clone, Default)]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    Jpeg,
+    WebP,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &str {
+        match self {
+            ImageFormat::Png => ".png",
+            ImageFormat::Jpeg => ".jpg",
+            ImageFormat::WebP => ".webp",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<ImageFormat> {
+        match s.to_lowercase().as_str() {
+            "png" => Some(ImageFormat::Png),
+            "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+            "webp" => Some(ImageFormat::WebP),
+            _ => None,
+        }
+    }
+
+    pub fn quality_args(&self) -> &str {
+        match self {
+            ImageFormat::Png => "",
+            ImageFormat::Jpeg => " -q:v 2",
+            ImageFormat::WebP => " -quality 90",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ThumbnailMode {
+    Frame {
+        timestamp: f64,
+    },
+    Series {
+        count: u16,
+        duration: f64,
+    },
+    ContactSheet {
+        columns: u16,
+        rows: u16,
+        duration: f64,
+        width: u16,
+        labels: bool,
+        header_file: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct ThumbnailOptions {
+    pub mode: ThumbnailMode,
+    pub format: ImageFormat,
+}
+
+const HEADER_HEIGHT: u16 = 40;
+
+impl ThumbnailOptions {
+    pub fn args(&self) -> String {
+        match &self.mode {
+            ThumbnailMode::Frame { .. } => " -frames:v 1".to_owned(),
+            // the rate is written as a ratio, a rounded interval is 0 for short clips
+            ThumbnailMode::Series { count, duration } => {
+                format!(r#" -vf "fps={}/{}" -frames:v {}"#, count, duration, count)
+            }
+            ThumbnailMode::ContactSheet {
+                columns,
+                rows,
+                duration,
+                width,
+                labels,
+                header_file,
+            } => {
+                let count = *columns as u32 * *rows as u32;
+                let mut filter = format!("fps={}/{},scale={}:-1", count, duration, width);
+                if *labels {
+                    filter.push_str(
+                        r#",drawtext=text='%{pts\:hms}':x=5:y=h-th-5:fontsize=16:fontcolor=white:box=1:boxcolor=black@0.6"#,
+                    );
+                }
+                filter.push_str(format!(",tile={}x{}:padding=4:margin=4", columns, rows).as_str());
+                if let Some(header) = header_file {
+                    filter.push_str(
+                        format!(
+                            ",pad=iw:ih+{h}:0:{h}:color=black,drawtext=textfile={}:x=10:y=12:fontsize=16:fontcolor=white",
+                            string_utils::escape_filter_value(header),
+                            h = HEADER_HEIGHT
+                        )
+                        .as_str(),
+                    );
+                }
+                format!(r#" -vf "{}" -frames:v 1"#, filter)
+            }
+        }
+    }
+
+    pub fn seek_args(&self) -> String {
+        match &self.mode {
+            ThumbnailMode::Frame { timestamp } => format!(" -ss {}", timestamp),
+            ThumbnailMode::Series { count, duration } => {
+                let offset = duration / *count as f64 / 2.0;
+                format!(" -ss {:.3}", offset)
+            }
+            ThumbnailMode::ContactSheet { .. } => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_not_overflow_large_contact_sheets() {
+        let options = ThumbnailOptions {
+            mode: ThumbnailMode::ContactSheet {
+                columns: 300,
+                rows: 300,
+                duration: 9000.0,
+                width: 64,
+                labels: false,
+                header_file: None,
+            },
+            format: ImageFormat::Png,
+        };
+        assert_eq!(
+            options.args(),
+            r#" -vf "fps=90000/9000,scale=64:-1,tile=300x300:padding=4:margin=4" -frames:v 1"#
+        );
+    }
+
+    #[test]
+    fn should_sample_short_clips() {
+        let short = ThumbnailOptions {
+            mode: ThumbnailMode::Series {
+                count: 10,
+                duration: 0.004,
+            },
+            format: ImageFormat::Png,
+        };
+        assert_eq!(short.args(), r#" -vf "fps=10/0.004" -frames:v 10"#);
+    }
+}
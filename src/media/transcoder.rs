@@ -1,11 +1,52 @@
+use crate::command::command_runner;
 use crate::command::ffmpeg_command;
-use crate::command::ffmpeg_command::{CommandType, FfmpegCommand, FfmpegCommandBuilderError};
+use crate::command::ffmpeg_command::{
+    CommandType, FfmpegCommand, FfmpegCommandBuilder, FfmpegCommandBuilderError,
+};
 use crate::error::TranscoderError;
 use crate::error::TranscoderError::InvalidCommand;
+use crate::media::animation::{AnimationFormat, AnimationOptions, DitherMode};
+use crate::media::audio_merge::{AudioInput, AudioMergeMode, AudioMergeOptions, DuckingOptions};
+use crate::media::audio_settings::{AudioQuality, Resampler, SampleFormat};
+use crate::media::channels;
+use crate::media::channels::{ChannelOperation, ChannelOptions, MonoSource};
+use crate::media::chapters;
+use crate::media::chapters::{ChapterFile, ChapterOperation};
 use crate::media::codecs;
 use crate::media::codecs::{AudioCodec, CodecAsString, CodecType, VideoCodec};
+use crate::media::crop;
+use crate::media::crop::Crop;
+use crate::media::cut_list;
+use crate::media::cut_list::{CutListOptions, CutMode};
+use crate::media::detection;
+use crate::media::detection::{KeepRanges, Timeline};
+use crate::media::frame_rate;
+use crate::media::frame_rate::RateChange;
+use crate::media::interlace;
+use crate::media::interlace::{Deinterlacer, ScanType};
+use crate::media::metadata;
+use crate::media::metadata::MetadataEdit;
+use crate::media::orientation::Transform;
+use crate::media::playback;
+use crate::media::playback::{LoopOptions, ReverseOptions};
+use crate::media::probe;
+use crate::media::scaling::{Padding, ScaleAlgorithm, ScalePolicy, Scaling, SourceSize};
+use crate::media::segment;
+use crate::media::segment::SegmentOptions;
+use crate::media::smart_cut;
+use crate::media::smart_cut::{CutPart, EncodeParams, SmartCutOptions};
+use crate::media::speed;
+use crate::media::speed::SpeedChange;
+use crate::media::stream_mapping::{StreamMapping, StreamSelection};
+use crate::media::subtitles;
+use crate::media::subtitles::{ExtractedSubtitle, SubtitleOperation, SubtitleStyle, SubtitleTrack};
+use crate::media::text_overlay;
+use crate::media::text_overlay::{OverlayContent, TextOverlay, TextStyle};
+use crate::media::thumbnail::{ImageFormat, ThumbnailMode, ThumbnailOptions};
+use crate::media::watermark::{Position, Watermark};
 use crate::{string_utils, user_input};
 use log::{debug, warn};
+use std::collections::HashMap;
 use std::error::Error;
 
 pub fn convert() -> Result<FfmpegCommand, Box<dyn Error>> {
@@ -14,6 +55,8 @@ pub fn convert() -> Result<FfmpegCommand, Box<dyn Error>> {
     if input.ends_with(&format) {
         return Err(TranscoderError::SameInputAndOutput.into());
     }
+    let stream_mapping = ask_stream_mapping(&input)?;
+    let video_filters = ask_deinterlace(&input)?.into_iter().collect::<Vec<_>>();
 
     let cmd = ffmpeg_command::builder()
         .command_type(CommandType::ConvertFormat)
@@ -21,6 +64,8 @@ pub fn convert() -> Result<FfmpegCommand, Box<dyn Error>> {
         .output_file(output)
         .audio_codec(AudioCodec::default())
         .video_codec(VideoCodec::default())
+        .stream_mapping(stream_mapping)
+        .video_filters(video_filters)
         .build();
 
     unwrap_ffmpeg_command(cmd)
@@ -29,6 +74,19 @@ pub fn convert() -> Result<FfmpegCommand, Box<dyn Error>> {
 pub fn compress() -> Result<FfmpegCommand, Box<dyn Error>> {
     let input = user_input::ask_input_file()?;
     let output = string_utils::change_file_extension(&input, "_compressed.mp4")?;
+    let stream_mapping = ask_stream_mapping(&input)?;
+    let deinterlace = ask_deinterlace(&input)?;
+    let crop = ask_auto_crop(&input)?;
+    let scaling = ask_scaling(&input, crop.as_ref())?;
+    let video_filters = deinterlace
+        .into_iter()
+        .chain(crop.iter().map(|c| c.filter()))
+        .collect::<Vec<_>>();
+    let mut temp_files = Vec::new();
+    let overlays = ask_watermark(&mut temp_files)?
+        .iter()
+        .map(|w| w.filter())
+        .collect::<Vec<_>>();
 
     let cmd = ffmpeg_command::builder()
         .command_type(CommandType::Compress)
@@ -36,6 +94,11 @@ pub fn compress() -> Result<FfmpegCommand, Box<dyn Error>> {
         .output_file(output)
         .audio_codec(AudioCodec::Aac)
         .video_codec(VideoCodec::Libx264)
+        .stream_mapping(stream_mapping)
+        .video_filters(video_filters)
+        .scaling(scaling)
+        .overlays(overlays)
+        .temp_files(temp_files)
         .build();
 
     unwrap_ffmpeg_command(cmd)
@@ -70,6 +133,17 @@ pub fn youtube_optimized() -> Result<FfmpegCommand, Box<dyn Error>> {
     }
 
     let output = string_utils::change_file_extension(&input, "_yt.mp4")?;
+    let stream_mapping = ask_stream_mapping(&input)?;
+    let deinterlace = ask_deinterlace(&input)?;
+    let video_filters = deinterlace
+        .into_iter()
+        .chain(ask_auto_crop(&input)?.iter().map(|c| c.filter()))
+        .collect::<Vec<_>>();
+    let mut temp_files = Vec::new();
+    let overlays = ask_watermark(&mut temp_files)?
+        .iter()
+        .map(|w| w.filter())
+        .collect::<Vec<_>>();
 
     let cmd = ffmpeg_command::builder()
         .command_type(CommandType::YoutubeOptimized)
@@ -77,6 +151,10 @@ pub fn youtube_optimized() -> Result<FfmpegCommand, Box<dyn Error>> {
         .output_file(output)
         .audio_codec(AudioCodec::Aac)
         .video_codec(VideoCodec::Libx264)
+        .stream_mapping(stream_mapping)
+        .video_filters(video_filters)
+        .overlays(overlays)
+        .temp_files(temp_files)
         .build();
 
     unwrap_ffmpeg_command(cmd)
@@ -93,13 +171,18 @@ pub fn multi_task() -> Result<FfmpegCommand, Box<dyn Error>> {
     println!("Provide audio codec (e.g. acc):");
     let audio_codec = read_input();
 
+    let stream_mapping = ask_stream_mapping(&input)?;
+    let video_filters = ask_deinterlace(&input)?.into_iter().collect::<Vec<_>>();
+
     let mut cmd = ffmpeg_command::builder();
     let cmd = cmd
         .command_type(CommandType::MultiTask)
         .input_file(input)
         .output_file(output)
         .video_codec(VideoCodec::Custom(video_codec))
-        .audio_codec(AudioCodec::Custom(audio_codec));
+        .audio_codec(AudioCodec::Custom(audio_codec.clone()))
+        .stream_mapping(stream_mapping)
+        .video_filters(video_filters);
 
     println!(
         "You will be asked a few optional parameters. Leave the input blank to skip any of them."
@@ -115,16 +198,37 @@ pub fn multi_task() -> Result<FfmpegCommand, Box<dyn Error>> {
             Err(_) => eprintln!("Invalid scale."),
         };
     }
-    println!("Audio bitrate (e.g. 128)");
-    let bitrate = read_input();
-    if !bitrate.is_empty() {
-        match bitrate.parse::<i16>() {
-            Ok(bitrate) => {
-                cmd.scale(bitrate);
-            }
-            Err(_) => eprintln!("Invalid bitrate."),
-        };
+    let opus = audio_codec.to_lowercase() == "libopus";
+    let mut vbr = false;
+    if !opus {
+        println!(
+            "Audio VBR quality (e.g. 2 for mp3, 1.5 for aac), leave blank for a constant bitrate:"
+        );
+        if let Some(quality) = user_input::read_optional("quality") {
+            cmd.audio_quality(AudioQuality::Vbr(quality));
+            vbr = true;
+        }
+    }
+    if !vbr {
+        println!("Audio bitrate (e.g. 128)");
+        let bitrate = read_input();
+        if !bitrate.is_empty() {
+            match bitrate.parse::<i16>() {
+                Ok(bitrate) => {
+                    cmd.audio_bitrate(bitrate);
+                }
+                Err(_) => eprintln!("Invalid bitrate."),
+            };
+        }
+    }
+    if opus {
+        println!("Opus VBR with the bitrate as a target (y/N)?");
+        if read_input().to_lowercase() == "y" {
+            cmd.audio_quality(AudioQuality::OpusVbr);
+        }
     }
+    ask_audio_settings(cmd, &audio_codec);
+
     println!("Preset (e.g. medium):");
     let preset = read_input();
     if !preset.is_empty() {
@@ -144,6 +248,1825 @@ pub fn multi_task() -> Result<FfmpegCommand, Box<dyn Error>> {
     unwrap_ffmpeg_command(cmd.build())
 }
 
+pub fn animation() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+
+    println!("Output format (gif/webp, default gif):");
+    let format = match user_input::read_input().to_lowercase().as_str() {
+        "" | "gif" => AnimationFormat::Gif,
+        "webp" => AnimationFormat::WebP,
+        _ => return Err(TranscoderError::UnsupportedOperation.into()),
+    };
+
+    println!(
+        "You will be asked a few optional parameters. Leave the input blank to use the default."
+    );
+    println!("Start time (e.g. 00:01:05 or 65):");
+    let start = user_input::read_optional_timestamp("start time");
+    println!("Duration (e.g. 5):");
+    let duration = user_input::read_optional_timestamp("duration");
+    println!("Frames per second (default 10):");
+    let fps = user_input::read_optional::<u8>("fps")
+        .filter(|v| *v > 0)
+        .unwrap_or(10);
+    println!("Width (default 480):");
+    let width = user_input::read_optional::<u16>("width")
+        .filter(|v| *v > 0)
+        .unwrap_or(480);
+
+    let mut dither = DitherMode::default();
+    if format == AnimationFormat::Gif {
+        println!(
+            "Dither mode (bayer/floyd_steinberg/sierra2/sierra2_4a/none, default sierra2_4a):"
+        );
+        let mode = user_input::read_input();
+        if !mode.is_empty() {
+            match DitherMode::parse(&mode) {
+                Some(mode) => dither = mode,
+                None => eprintln!("Invalid dither mode."),
+            }
+        }
+    }
+
+    let (suffix, temp_files) = match format {
+        AnimationFormat::Gif => (
+            "_animated.gif",
+            vec![string_utils::temp_file_path("palette.png")],
+        ),
+        AnimationFormat::WebP => ("_animated.webp", Vec::new()),
+    };
+    let output = string_utils::change_file_extension(&input, suffix)?;
+    let options = AnimationOptions {
+        format,
+        start,
+        duration,
+        fps,
+        width,
+        dither,
+        palette_file: temp_files.first().cloned().unwrap_or_default(),
+    };
+
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::Animation(options))
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::default())
+        .video_codec(VideoCodec::default())
+        .temp_files(temp_files)
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn thumbnails() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+
+    println!("Chose a thumbnail type:");
+    println!("1. Single frame at a timestamp.");
+    println!("2. Evenly spaced thumbnails.");
+    println!("3. Contact sheet.");
+    let choice = user_input::read_input();
+
+    println!("Image format (png/jpeg/webp, default png):");
+    let format = user_input::read_input();
+    let format = match format.is_empty() {
+        true => ImageFormat::default(),
+        false => ImageFormat::parse(&format).ok_or(TranscoderError::UnsupportedOperation)?,
+    };
+
+    let mut temp_files = Vec::new();
+    let (mode, suffix) = match choice.as_str() {
+        "1" => {
+            println!("Timestamp (e.g. 00:01:05 or 65):");
+            let timestamp = string_utils::parse_timestamp(&user_input::read_input())?;
+            (ThumbnailMode::Frame { timestamp }, "_frame".to_owned())
+        }
+        "2" => {
+            println!("Number of thumbnails (default 10):");
+            let count = user_input::read_optional::<u16>("number of thumbnails")
+                .filter(|v| *v > 0)
+                .unwrap_or(10);
+            let duration = probe_duration(&input)?;
+            (
+                ThumbnailMode::Series { count, duration },
+                "_thumb_%03d".to_owned(),
+            )
+        }
+        "3" => {
+            println!("Columns (default 4):");
+            let columns = user_input::read_optional::<u16>("columns")
+                .filter(|v| *v > 0)
+                .unwrap_or(4);
+            println!("Rows (default 4):");
+            let rows = user_input::read_optional::<u16>("rows")
+                .filter(|v| *v > 0)
+                .unwrap_or(4);
+            println!("Thumbnail width (default 320):");
+            let width = user_input::read_optional::<u16>("width")
+                .filter(|v| *v > 0)
+                .unwrap_or(320);
+            println!("Add timestamp labels (Y/n)?");
+            let labels = user_input::read_input().to_lowercase() != "n";
+            println!("Add file info header (Y/n)?");
+            let header = user_input::read_input().to_lowercase() != "n";
+
+            let info = probe::probe(&input)?;
+            let duration = info
+                .duration
+                .filter(|duration| *duration > 0.0)
+                .ok_or(TranscoderError::ProbeFailed(input.clone()))?;
+            let header_file = match header {
+                true => {
+                    let file = string_utils::temp_file_path("header.txt");
+                    std::fs::write(&file, file_info_header(&input, &info))?;
+                    temp_files.push(file.clone());
+                    Some(file)
+                }
+                false => None,
+            };
+            let mode = ThumbnailMode::ContactSheet {
+                columns,
+                rows,
+                duration,
+                width,
+                labels,
+                header_file,
+            };
+            (mode, "_contact_sheet".to_owned())
+        }
+        _ => return Err(TranscoderError::UnsupportedOperation.into()),
+    };
+
+    let output = string_utils::change_file_extension(&input, &(suffix + format.extension()))?;
+
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::Thumbnail(ThumbnailOptions { mode, format }))
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::default())
+        .video_codec(VideoCodec::default())
+        .temp_files(temp_files)
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn subtitles() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+
+    println!("Chose a subtitle operation:");
+    println!("1. Burn subtitles into the video.");
+    println!("2. Add subtitles as soft tracks.");
+    println!("3. Extract subtitle streams.");
+    let (operation, output) = match user_input::read_input().as_str() {
+        "1" => burn_in_subtitles(&input)?,
+        "2" => mux_subtitles(&input)?,
+        "3" => extract_subtitles(&input)?,
+        _ => return Err(TranscoderError::UnsupportedOperation.into()),
+    };
+    let video_filters = match operation {
+        SubtitleOperation::BurnIn { .. } => ask_deinterlace(&input)?.into_iter().collect(),
+        _ => Vec::new(),
+    };
+
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::Subtitles(operation))
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::Custom("copy".to_owned()))
+        .video_codec(VideoCodec::Libx264)
+        .video_filters(video_filters)
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+fn burn_in_subtitles(input: &str) -> Result<(SubtitleOperation, String), Box<dyn Error>> {
+    let subtitle_file = user_input::ask_existing_file("Provide subtitle path (srt/ass):")?;
+
+    let mut style = SubtitleStyle::default();
+    if !subtitles::is_ass(&subtitle_file) {
+        println!(
+            "You will be asked a few optional style parameters. Leave the input blank to skip any of them."
+        );
+        println!("Font name (e.g. Arial):");
+        style.font_name = Some(user_input::read_input()).filter(|f| !f.is_empty());
+        println!("Font size (e.g. 24):");
+        style.font_size = user_input::read_optional("font size");
+        println!("Primary colour in ASS format (e.g. &H00FFFFFF):");
+        style.primary_colour = Some(user_input::read_input()).filter(|c| !c.is_empty());
+        println!("Outline width (e.g. 1):");
+        style.outline = user_input::read_optional("outline width");
+    }
+
+    let output = string_utils::change_file_extension(input, "_subbed.mp4")?;
+    let operation = SubtitleOperation::BurnIn {
+        subtitle_file,
+        style,
+    };
+    Ok((operation, output))
+}
+
+fn mux_subtitles(input: &str) -> Result<(SubtitleOperation, String), Box<dyn Error>> {
+    println!("Output container (mp4/mkv, default mkv):");
+    let container = user_input::read_input();
+    let container = match container.is_empty() {
+        true => "mkv".to_owned(),
+        false => container,
+    };
+    let output = string_utils::change_file_extension(input, &format!("_subs.{}", container))?;
+
+    let mut tracks = Vec::new();
+    loop {
+        let file = user_input::ask_existing_file("Provide subtitle path (srt/ass):")?;
+        if subtitles::mux_codec(&output, &file).is_none() {
+            return Err(TranscoderError::UnsupportedOperation.into());
+        }
+        println!("Language code (e.g. eng), leave blank to skip:");
+        let language = Some(user_input::read_input()).filter(|l| !l.is_empty());
+        tracks.push(SubtitleTrack { file, language });
+
+        println!("Add another subtitle track (y/N)?");
+        if user_input::read_input().to_lowercase() != "y" {
+            break;
+        }
+    }
+
+    let existing_subtitles = probe::probe(input)?.streams_of_type("subtitle").count();
+    let operation = SubtitleOperation::Mux {
+        tracks,
+        existing_subtitles,
+    };
+    Ok((operation, output))
+}
+
+fn extract_subtitles(input: &str) -> Result<(SubtitleOperation, String), Box<dyn Error>> {
+    println!("Subtitle format (srt/vtt, default srt):");
+    let format = user_input::read_input();
+    let format = match format.as_str() {
+        "" | "srt" => "srt",
+        "vtt" => "vtt",
+        _ => return Err(TranscoderError::UnsupportedOperation.into()),
+    };
+
+    let info = probe::probe(input)?;
+    let mut streams = Vec::new();
+    for (n, stream) in info.streams_of_type("subtitle").enumerate() {
+        if subtitles::is_bitmap_codec(&stream.codec_name) {
+            warn!(
+                "Skipping bitmap subtitle stream #{} ({}).",
+                stream.index, stream.codec_name
+            );
+            continue;
+        }
+        let suffix = format!(".{}.{}", stream.language().unwrap_or("und"), n);
+        let output_file =
+            string_utils::change_file_extension(input, &format!("{}.{}", suffix, format))?;
+        streams.push(ExtractedSubtitle {
+            stream_index: stream.index,
+            output_file,
+        });
+    }
+
+    if streams.is_empty() {
+        let reason = "No text subtitle streams found.";
+        return Err(TranscoderError::AbortTranscoding(reason.to_owned()).into());
+    }
+    let output = streams[0].output_file.clone();
+    Ok((SubtitleOperation::Extract { streams }, output))
+}
+
+pub fn edit_metadata() -> Result<(), Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+    metadata::print_tags(&probe::probe(&input)?);
+
+    println!("Chose a metadata operation:");
+    println!("1. Set tags.");
+    println!("2. Strip all metadata (including GPS location).");
+    let edit = match user_input::read_input().as_str() {
+        "1" => {
+            println!("Leave the input blank to keep the current value.");
+            let mut tags = Vec::new();
+            for tag in metadata::EDITABLE_TAGS {
+                println!("{}:", tag);
+                let value = user_input::read_input();
+                if !value.is_empty() {
+                    tags.push((tag.to_owned(), value));
+                }
+            }
+            println!("Audio and subtitle language (e.g. eng):");
+            let language = Some(user_input::read_input()).filter(|l| !l.is_empty());
+            MetadataEdit::Set { tags, language }
+        }
+        "2" => MetadataEdit::StripAll,
+        _ => return Err(TranscoderError::UnsupportedOperation.into()),
+    };
+
+    let ext = string_utils::find_file_extension(&input)?;
+    let output = string_utils::change_file_extension(&input, &format!("_metadata{}", ext))?;
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::Metadata(edit.clone()))
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::default())
+        .video_codec(VideoCodec::default())
+        .build();
+    let cmd = unwrap_ffmpeg_command(cmd)?;
+    command_runner::run_command(&cmd)?;
+
+    let info = probe::probe(cmd.output_file())?;
+    let problems = edit.verify(&info);
+    if problems.is_empty() {
+        println!("Metadata verified successfully.");
+    }
+    for problem in problems {
+        warn!("{}", problem);
+    }
+    Ok(())
+}
+
+pub fn chapters() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+    let info = probe::probe(&input)?;
+    let duration = info
+        .duration
+        .ok_or(TranscoderError::ProbeFailed(input.clone()))?;
+
+    println!("Chapters found in the file: {}", info.chapters.len());
+    for chapter in info.chapters.iter() {
+        println!(
+            "{} - {} {}",
+            string_utils::format_timestamp(chapter.start),
+            string_utils::format_timestamp(chapter.end),
+            chapter.title
+        );
+    }
+
+    println!("Chose a chapter operation:");
+    println!("1. Write chapters from a text/CSV file.");
+    println!("2. Split into one file per chapter.");
+    let mut temp_files = Vec::new();
+    let (operation, output) = match user_input::read_input().as_str() {
+        "1" => {
+            let list = user_input::ask_existing_file(
+                "Provide chapter list path (lines of 'timestamp title' or 'timestamp,title'):",
+            )?;
+            let chapters = chapters::parse_chapter_list(&std::fs::read_to_string(list)?, duration)?;
+            let metadata_file = string_utils::temp_file_path("chapters.txt");
+            std::fs::write(&metadata_file, chapters::to_ffmetadata(&chapters))?;
+            temp_files.push(metadata_file.clone());
+
+            let ext = string_utils::find_file_extension(&input)?;
+            let output = string_utils::change_file_extension(&input, &format!("_chapters{}", ext))?;
+            (ChapterOperation::Write { metadata_file }, output)
+        }
+        "2" => {
+            if info.chapters.is_empty() {
+                let reason = "The file has no chapters.";
+                return Err(TranscoderError::AbortTranscoding(reason.to_owned()).into());
+            }
+            let ext = string_utils::find_file_extension(&input)?;
+            let mut outputs = Vec::new();
+            for (i, chapter) in info.chapters.iter().enumerate() {
+                let suffix = format!(
+                    "_{:02}_{}{}",
+                    i + 1,
+                    chapters::file_name_part(&chapter.title),
+                    ext
+                );
+                outputs.push(ChapterFile {
+                    chapter: chapter.clone(),
+                    output_file: string_utils::change_file_extension(&input, &suffix)?,
+                });
+            }
+            let output = outputs[0].output_file.clone();
+            (ChapterOperation::Split { outputs }, output)
+        }
+        _ => return Err(TranscoderError::UnsupportedOperation.into()),
+    };
+
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::Chapters(operation))
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::default())
+        .video_codec(VideoCodec::default())
+        .temp_files(temp_files)
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn split() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+
+    println!("Split by:");
+    println!("1. Segment duration.");
+    println!("2. Maximum size per piece.");
+    let segment_time = match user_input::read_input().as_str() {
+        "1" => {
+            println!("Segment duration (e.g. 00:10:00 or 600):");
+            string_utils::parse_timestamp(&user_input::read_input())?
+        }
+        "2" => {
+            println!("Maximum size in MB (e.g. 500):");
+            let size =
+                user_input::read_optional::<f64>("size").ok_or(TranscoderError::InvalidCommand)?;
+            let info = probe::probe(&input)?;
+            let bit_rate = info
+                .bit_rate
+                .ok_or(TranscoderError::ProbeFailed(input.clone()))?;
+            let segment_time = segment::segment_time_for_size(size, bit_rate);
+            println!(
+                "Pieces will be about {} long. Cuts happen at keyframes, so sizes are approximate.",
+                string_utils::format_timestamp(segment_time)
+            );
+            segment_time
+        }
+        _ => return Err(TranscoderError::UnsupportedOperation.into()),
+    };
+    if segment_time <= 0.0 {
+        return Err(TranscoderError::InvalidCommand.into());
+    }
+
+    let ext = string_utils::find_file_extension(&input)?;
+    let output = string_utils::change_file_extension(&input, &format!("_part%03d{}", ext))?;
+
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::Segment(SegmentOptions::Every(segment_time)))
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::default())
+        .video_codec(VideoCodec::default())
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn cut_list() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+    let list = user_input::ask_existing_file(
+        "Provide cut list path (csv lines of 'in,out' or 'source,in,out', or a CMX3600 edl):",
+    )?;
+    let content = std::fs::read_to_string(&list)?;
+
+    let clips = match list.to_lowercase().ends_with(".edl") {
+        true => {
+            let fps = probe::probe(&input)?
+                .video_stream()
+                .and_then(|s| s.frame_rate)
+                .ok_or(TranscoderError::ProbeFailed(input.clone()))?;
+            cut_list::parse_edl(&content, &input, fps)?
+        }
+        false => cut_list::parse_csv(&content, &input)?,
+    };
+
+    let mut durations = HashMap::new();
+    let mut has_audio = true;
+    for clip in clips.iter() {
+        if !durations.contains_key(&clip.source) {
+            let info = probe::probe(&clip.source)?;
+            let duration = info
+                .duration
+                .ok_or(TranscoderError::ProbeFailed(clip.source.clone()))?;
+            durations.insert(clip.source.clone(), duration);
+            has_audio &= info.streams_of_type("audio").next().is_some();
+        }
+    }
+    cut_list::validate(&clips, &durations)?;
+    println!("Found {} valid ranges.", clips.len());
+
+    println!("Chose a cut mode:");
+    println!("1. Lossless (fast, cuts snap to keyframes).");
+    println!("2. Frame accurate (re-encodes, sources must share resolution).");
+    let mut temp_files = Vec::new();
+    let mut video_filters = Vec::new();
+    let (mode, suffix) = match user_input::read_input().as_str() {
+        "1" => {
+            let ext = string_utils::find_file_extension(&input)?;
+            let parts = (1..=clips.len())
+                .map(|i| string_utils::temp_file_path(&format!("part{}{}", i, ext)))
+                .collect::<Vec<_>>();
+            let list_file = string_utils::temp_file_path("concat.txt");
+            std::fs::write(&list_file, cut_list::concat_list(&parts))?;
+            temp_files.extend(parts.iter().cloned());
+            temp_files.push(list_file.clone());
+            (
+                CutMode::Lossless { parts, list_file },
+                format!("_cut{}", ext),
+            )
+        }
+        "2" => {
+            if !has_audio {
+                println!("Not every source has audio, the result will have none.");
+            }
+            video_filters.extend(ask_deinterlace(&input)?);
+            (CutMode::FrameAccurate { has_audio }, "_cut.mp4".to_owned())
+        }
+        _ => return Err(TranscoderError::UnsupportedOperation.into()),
+    };
+    let output = string_utils::change_file_extension(&input, &suffix)?;
+
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::CutList(CutListOptions { clips, mode }))
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::Aac)
+        .video_codec(VideoCodec::Libx264)
+        .video_filters(video_filters)
+        .temp_files(temp_files)
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn smart_cut() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+    let info = probe::probe(&input)?;
+    let duration = info
+        .duration
+        .ok_or(TranscoderError::ProbeFailed(input.clone()))?;
+
+    println!("Cut start (e.g. 00:01:05.250):");
+    let start = string_utils::parse_timestamp(&user_input::read_input())?;
+    println!("Cut end (e.g. 00:02:10):");
+    let end = string_utils::parse_timestamp(&user_input::read_input())?;
+    if start >= end || end > duration {
+        return Err(TranscoderError::InvalidCommand.into());
+    }
+
+    let keyframes = smart_cut::probe_keyframes(&input, start, end);
+    let ranges = smart_cut::plan(start, end, &keyframes);
+    let ext = string_utils::find_file_extension(&input)?;
+    let mut params = EncodeParams::from_probe(&info);
+    if ![".mp4", ".mov", ".m4v"].contains(&ext.to_lowercase().as_str()) {
+        params.timescale = None;
+    }
+    let part_ext = params.part_extension(&ext);
+    let parts = ranges
+        .into_iter()
+        .enumerate()
+        .map(|(i, (start, end, copy))| CutPart {
+            start,
+            end,
+            copy,
+            file: string_utils::temp_file_path(&format!("smart{}{}", i, part_ext)),
+        })
+        .collect::<Vec<_>>();
+    for part in parts.iter() {
+        debug!(
+            "{} {} - {}",
+            if part.copy { "Copy" } else { "Re-encode" },
+            part.start,
+            part.end
+        );
+    }
+
+    let list_file = string_utils::temp_file_path("smart_concat.txt");
+    let part_files = parts.iter().map(|p| p.file.clone()).collect::<Vec<_>>();
+    std::fs::write(&list_file, cut_list::concat_list(&part_files))?;
+    let mut temp_files = part_files;
+    temp_files.push(list_file.clone());
+
+    let output = string_utils::change_file_extension(&input, &format!("_smartcut{}", ext))?;
+    let options = SmartCutOptions {
+        parts,
+        params,
+        list_file,
+    };
+
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::SmartCut(options))
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::default())
+        .video_codec(VideoCodec::default())
+        .temp_files(temp_files)
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn merge_audio() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+
+    println!("Chose an audio operation:");
+    println!("1. Replace the audio.");
+    println!("2. Add as extra audio tracks.");
+    println!("3. Mix with the original audio.");
+    println!("4. Mix music ducked under speech.");
+    let choice = user_input::read_input();
+    let volumes = choice == "3" || choice == "4";
+
+    let mut inputs = Vec::new();
+    loop {
+        let file =
+            user_input::ask_existing_file("Provide audio path (e.g. /some/directory/audio.wav):")?;
+        let mut audio = AudioInput {
+            file,
+            volume: 1.0,
+            language: None,
+            title: None,
+        };
+        if volumes {
+            println!("Volume of this audio (default 1.0, e.g. 0.3 for a music bed):");
+            audio.volume = user_input::read_optional("volume").unwrap_or(1.0);
+        } else {
+            println!("Language code (e.g. eng), leave blank to skip:");
+            audio.language = Some(user_input::read_input()).filter(|l| !l.is_empty());
+            println!("Track title, leave blank to skip:");
+            audio.title = Some(user_input::read_input()).filter(|t| !t.is_empty());
+        }
+        inputs.push(audio);
+
+        if choice == "4" {
+            break;
+        }
+        println!("Add another audio file (y/N)?");
+        if user_input::read_input().to_lowercase() != "y" {
+            break;
+        }
+    }
+
+    let mode = match choice.as_str() {
+        "1" => AudioMergeMode::Replace,
+        "2" => AudioMergeMode::AddTracks {
+            existing_tracks: probe::probe(&input)?.streams_of_type("audio").count(),
+        },
+        "3" => {
+            println!("Volume of the original audio (default 1.0):");
+            let original_volume = user_input::read_optional("volume").unwrap_or(1.0);
+            AudioMergeMode::Mix { original_volume }
+        }
+        "4" => AudioMergeMode::Duck(ask_ducking_options(&input)?),
+        _ => return Err(TranscoderError::UnsupportedOperation.into()),
+    };
+
+    println!(
+        "Audio offset in milliseconds (e.g. 250 to delay, -250 to advance), leave blank for none:"
+    );
+    let offset_ms = user_input::read_optional("offset").unwrap_or(0);
+
+    let ext = string_utils::find_file_extension(&input)?;
+    let output = string_utils::change_file_extension(&input, &format!("_audio{}", ext))?;
+    let options = AudioMergeOptions {
+        mode,
+        inputs,
+        offset_ms,
+    };
+
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::AudioMerge(options))
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::Aac)
+        .video_codec(VideoCodec::Custom("copy".to_owned()))
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn channels() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+    let info = probe::probe(&input)?;
+    let audio_streams = info.streams_of_type("audio").collect::<Vec<_>>();
+    if audio_streams.is_empty() {
+        return Err(TranscoderError::AbortTranscoding("No audio streams found.".to_owned()).into());
+    }
+
+    let mut stream = 0;
+    if audio_streams.len() > 1 {
+        println!("Audio streams found in the file:");
+        for (i, audio) in audio_streams.iter().enumerate() {
+            println!("{}. {}", i, audio.describe());
+        }
+        println!("Audio stream to use (default 0):");
+        stream = user_input::read_optional("stream")
+            .filter(|s| *s < audio_streams.len())
+            .unwrap_or(0);
+    }
+    let audio = audio_streams[stream];
+    let layout = channels::resolve_layout(audio.channel_layout.as_deref(), audio.channels)
+        .ok_or_else(|| {
+            TranscoderError::AbortTranscoding(format!(
+                "Unsupported channel layout: {}.",
+                audio.channel_layout.as_deref().unwrap_or("unknown")
+            ))
+        })?;
+    let layout_channels = channels::layout_channels(&layout).unwrap_or_default();
+    println!(
+        "Channel layout: {} ({})",
+        layout,
+        layout_channels.join(", ")
+    );
+
+    println!("Chose a channel operation:");
+    println!("1. Downmix to stereo.");
+    println!("2. Split into mono files.");
+    println!("3. Dual mono to mono.");
+    println!("4. Swap or remap channels.");
+    let ext = string_utils::find_file_extension(&input)?;
+    let (operation, output, audio_codec) = match user_input::read_input().as_str() {
+        "1" if layout_channels.len() > 2 => {
+            let output = string_utils::change_file_extension(&input, &format!("_stereo{}", ext))?;
+            (ChannelOperation::Downmix, output, AudioCodec::Aac)
+        }
+        "2" if layout_channels.len() > 1 => {
+            let outputs = layout_channels
+                .iter()
+                .map(|c| string_utils::change_file_extension(&input, &format!("_{}.wav", c)))
+                .collect::<Result<Vec<_>, _>>()?;
+            let output = outputs[0].clone();
+            let codec = AudioCodec::Custom("pcm_s24le".to_owned());
+            (ChannelOperation::SplitMono { outputs }, output, codec)
+        }
+        "3" if layout == "stereo" => {
+            println!("Keep which channel?");
+            println!("1. Left.");
+            println!("2. Right.");
+            println!("3. Average of both.");
+            let source = match user_input::read_input().as_str() {
+                "1" => MonoSource::Left,
+                "2" => MonoSource::Right,
+                _ => MonoSource::Both,
+            };
+            let output = string_utils::change_file_extension(&input, &format!("_mono{}", ext))?;
+            (ChannelOperation::ToMono(source), output, AudioCodec::Aac)
+        }
+        "4" if layout_channels.len() > 1 => {
+            println!(
+                "New channel order using the source channels (e.g. {}):",
+                layout_channels
+                    .iter()
+                    .rev()
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join(",")
+            );
+            let order = channels::parse_channel_order(&user_input::read_input(), &layout)?;
+            let output = string_utils::change_file_extension(&input, &format!("_remap{}", ext))?;
+            (ChannelOperation::Remap { order }, output, AudioCodec::Aac)
+        }
+        _ => return Err(TranscoderError::UnsupportedOperation.into()),
+    };
+
+    let options = ChannelOptions {
+        stream,
+        layout,
+        operation,
+    };
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::Channels(options))
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(audio_codec)
+        .video_codec(VideoCodec::Custom("copy".to_owned()))
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn remove_silence() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+    let info = probe::probe(&input)?;
+    let duration = info
+        .duration
+        .ok_or(TranscoderError::ProbeFailed(input.clone()))?;
+    if info.streams_of_type("audio").next().is_none() {
+        return Err(TranscoderError::AbortTranscoding("No audio streams found.".to_owned()).into());
+    }
+
+    println!("Noise threshold in dB (default -30):");
+    let noise_db = user_input::read_optional("threshold").unwrap_or(-30.0);
+    println!("Minimum silence length in seconds (default 1.0):");
+    let min_duration = user_input::read_optional("length").unwrap_or(1.0);
+
+    println!("Detecting silence...");
+    let silences = detection::detect_silence(&input, noise_db, min_duration, duration);
+    if silences.is_empty() {
+        return Err(TranscoderError::AbortTranscoding("No silence found.".to_owned()).into());
+    }
+    for silence in silences.iter() {
+        println!(
+            "{} - {} ({:.1}s)",
+            string_utils::format_timestamp(silence.start),
+            string_utils::format_timestamp(silence.end),
+            silence.duration()
+        );
+    }
+
+    println!("Longest gap to keep in seconds, 0 removes silences completely (default 0.5):");
+    let max_gap = user_input::read_optional("gap").unwrap_or(0.5_f64).max(0.0);
+    let keep = detection::keep_ranges(&silences, duration, max_gap);
+    let kept = keep.iter().map(|range| range.duration()).sum::<f64>();
+    println!(
+        "Found {} silences, the output will be {} instead of {}. Proceed (Y/n)?",
+        silences.len(),
+        string_utils::format_timestamp(kept),
+        string_utils::format_timestamp(duration)
+    );
+    if user_input::read_input().to_lowercase() == "n" {
+        return Err(
+            TranscoderError::AbortTranscoding("Silence removal cancelled.".to_owned()).into(),
+        );
+    }
+
+    let ext = string_utils::find_file_extension(&input)?;
+    let output = string_utils::change_file_extension(&input, &format!("_nosilence{}", ext))?;
+    let has_video = info.video_stream().is_some();
+    let removal = KeepRanges::new(keep, has_video, true)?;
+    let video_filters = match has_video {
+        true => ask_deinterlace(&input)?.into_iter().collect(),
+        false => Vec::new(),
+    };
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::KeepRanges(removal))
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::Aac)
+        .video_codec(VideoCodec::Libx264)
+        .video_filters(video_filters)
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn analyze_scenes() -> Result<(), Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+    let info = probe::probe(&input)?;
+    let duration = info
+        .duration
+        .ok_or(TranscoderError::ProbeFailed(input.clone()))?;
+    if info.video_stream().is_none() {
+        return Err(TranscoderError::AbortTranscoding("No video stream found.".to_owned()).into());
+    }
+    let has_audio = info.streams_of_type("audio").next().is_some();
+
+    println!("Scene change threshold from 0 to 1 (default 0.4, lower finds more scenes):");
+    let threshold = user_input::read_optional("threshold").unwrap_or(0.4);
+    println!("Minimum black length in seconds (default 0.5):");
+    let min_black = user_input::read_optional("length").unwrap_or(0.5);
+
+    println!("Analyzing, this decodes the whole file...");
+    let cuts = detection::detect_scenes(&input, threshold);
+    let black = detection::detect_black(&input, min_black, 0.1);
+    let silence = match has_audio {
+        true => detection::detect_silence(&input, -50.0, min_black, duration),
+        false => Vec::new(),
+    };
+    let timeline = Timeline {
+        duration,
+        scenes: detection::scene_ranges(&cuts, duration),
+        black,
+        silence,
+    };
+    print!("{}", timeline.to_text());
+
+    println!("Save the timeline report as text and JSON (y/N)?");
+    if user_input::read_input().to_lowercase() == "y" {
+        let text_file = string_utils::change_file_extension(&input, "_timeline.txt")?;
+        let json_file = string_utils::change_file_extension(&input, "_timeline.json")?;
+        std::fs::write(&text_file, timeline.to_text())?;
+        std::fs::write(&json_file, timeline.to_json())?;
+        println!("Saved {} and {}.", text_file, json_file);
+    }
+
+    println!("Chose an action:");
+    println!("1. Split at scene changes.");
+    println!("2. Trim leading and trailing black and silence.");
+    println!("0. Done.");
+    let ext = string_utils::find_file_extension(&input)?;
+    let (command_type, output) = match user_input::read_input().as_str() {
+        "1" if !cuts.is_empty() => (
+            CommandType::Segment(SegmentOptions::At(cuts)),
+            string_utils::change_file_extension(&input, &format!("_scene%03d{}", ext))?,
+        ),
+        "2" => {
+            let silence = Some(timeline.silence.as_slice()).filter(|_| has_audio);
+            let range = detection::trim_range(&timeline.black, silence, duration);
+            if range.start <= 0.0 && range.end >= duration {
+                println!("Nothing to trim.");
+                return Ok(());
+            }
+            println!(
+                "Keeping {} - {}.",
+                string_utils::format_timestamp(range.start),
+                string_utils::format_timestamp(range.end)
+            );
+            let keep = KeepRanges::new(vec![range], true, has_audio)?;
+            (
+                CommandType::KeepRanges(keep),
+                string_utils::change_file_extension(&input, &format!("_trimmed{}", ext))?,
+            )
+        }
+        "1" => {
+            println!("No scene changes found.");
+            return Ok(());
+        }
+        _ => return Ok(()),
+    };
+    let video_filters = ask_deinterlace(&input)?.into_iter().collect::<Vec<_>>();
+
+    let cmd = ffmpeg_command::builder()
+        .command_type(command_type)
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::Aac)
+        .video_codec(VideoCodec::Libx264)
+        .video_filters(video_filters)
+        .build();
+    command_runner::run_command(&unwrap_ffmpeg_command(cmd)?)?;
+    Ok(())
+}
+
+pub fn remove_black_bars() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+    let crop = detect_and_confirm_crop(&input)?.ok_or(TranscoderError::AbortTranscoding(
+        "No crop to apply.".to_owned(),
+    ))?;
+
+    let mut video_filters = ask_deinterlace(&input)?.into_iter().collect::<Vec<_>>();
+    video_filters.push(crop.filter());
+
+    let ext = string_utils::find_file_extension(&input)?;
+    let output = string_utils::change_file_extension(&input, &format!("_cropped{}", ext))?;
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::Filter)
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::Custom("copy".to_owned()))
+        .video_codec(VideoCodec::Libx264)
+        .video_filters(video_filters)
+        .preset("medium".to_owned())
+        .crf(18)
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn orientation() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+    let info = probe::probe(&input)?;
+    let video = info
+        .video_stream()
+        .ok_or(TranscoderError::AbortTranscoding(
+            "No video stream found.".to_owned(),
+        ))?;
+    let current = video.display_rotation();
+    println!("Current rotation: {} degrees clockwise.", current);
+
+    println!("Chose an orientation operation:");
+    println!("1. Rotate 90 degrees clockwise.");
+    println!("2. Rotate 180 degrees.");
+    println!("3. Rotate 90 degrees counter-clockwise.");
+    println!("4. Flip horizontally.");
+    println!("5. Flip vertically.");
+    println!("6. Normalize to 0 (apply the current rotation to the picture).");
+    let transform = match user_input::read_input().as_str() {
+        "1" => Some(Transform::Rotate(90)),
+        "2" => Some(Transform::Rotate(180)),
+        "3" => Some(Transform::Rotate(270)),
+        "4" => Some(Transform::HorizontalFlip),
+        "5" => Some(Transform::VerticalFlip),
+        "6" if current == 0 => {
+            return Err(
+                TranscoderError::AbortTranscoding("Rotation is already 0.".to_owned()).into(),
+            )
+        }
+        "6" => None,
+        _ => return Err(TranscoderError::UnsupportedOperation.into()),
+    };
+
+    let lossless = match transform {
+        Some(_) => {
+            println!("1. Lossless, only change the rotation metadata.");
+            println!("2. Re-encode the picture.");
+            user_input::read_input() == "1"
+        }
+        None => false,
+    };
+
+    let ext = string_utils::find_file_extension(&input)?;
+    let output = string_utils::change_file_extension(&input, &format!("_rotated{}", ext))?;
+    let mut cmd = ffmpeg_command::builder();
+    cmd.input_file(input.clone())
+        .output_file(output)
+        .audio_codec(AudioCodec::Custom("copy".to_owned()))
+        .video_codec(VideoCodec::Libx264);
+    match transform {
+        Some(transform) if lossless => {
+            cmd.command_type(CommandType::Orientation(transform.display_matrix(current)));
+        }
+        // ffmpeg applies the current rotation while decoding, so only the new transform is added
+        _ => {
+            let mut filters = ask_deinterlace(&input)?.into_iter().collect::<Vec<_>>();
+            filters.extend(transform.map(|t| t.filters()).unwrap_or_default());
+            cmd.command_type(CommandType::Filter)
+                .video_filters(filters)
+                .preset("medium".to_owned())
+                .crf(18);
+        }
+    }
+
+    unwrap_ffmpeg_command(cmd.build())
+}
+
+pub fn frame_rate() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+    let info = probe::probe(&input)?;
+    let nominal = info
+        .video_stream()
+        .and_then(|v| v.frame_rate)
+        .ok_or(TranscoderError::ProbeFailed(input.clone()))?;
+
+    println!("Checking frame timestamps...");
+    let timestamps = frame_rate::probe_timestamps(&input);
+    match frame_rate::analyze(&timestamps, nominal) {
+        Some(timing) => println!(
+            "Nominal {:.3} fps, measured {:.3} - {:.3} fps (average {:.3}), {:.1}% irregular frame intervals: {} frame rate.",
+            timing.nominal,
+            timing.min,
+            timing.max,
+            timing.average,
+            timing.irregular * 100.0,
+            if timing.is_variable() { "variable" } else { "constant" }
+        ),
+        None => warn!("Could not read the frame timestamps."),
+    }
+
+    println!("Chose a frame rate operation:");
+    println!("1. Convert to constant frame rate.");
+    println!("2. Change frame rate (drop or duplicate frames).");
+    println!("3. Change frame rate with motion interpolated frames (smooth, slow to encode).");
+    let choice = user_input::read_input();
+    if !["1", "2", "3"].contains(&choice.as_str()) {
+        return Err(TranscoderError::UnsupportedOperation.into());
+    }
+    let default_rate = format!("{}", (nominal * 1000.0).round() / 1000.0);
+    println!(
+        "Target frame rate (e.g. 30 or 30000/1001, default {}):",
+        default_rate
+    );
+    let value = user_input::read_input();
+    let rate = match value.is_empty() {
+        true => default_rate,
+        false => frame_rate::parse_rate(&value).ok_or(TranscoderError::InvalidCommand)?,
+    };
+    let change = match choice.as_str() {
+        "3" => RateChange::Interpolate(rate),
+        _ => RateChange::Fps(rate),
+    };
+    let mut video_filters = ask_deinterlace(&input)?.into_iter().collect::<Vec<_>>();
+    video_filters.push(change.filter());
+
+    let ext = string_utils::find_file_extension(&input)?;
+    let suffix = if choice == "1" { "_cfr" } else { "_fps" };
+    let output = string_utils::change_file_extension(&input, &format!("{}{}", suffix, ext))?;
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::Filter)
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::Custom("copy".to_owned()))
+        .video_codec(VideoCodec::Libx264)
+        .video_filters(video_filters)
+        .preset("medium".to_owned())
+        .crf(18)
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn speed() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+
+    println!("Chose a speed operation:");
+    println!("1. Change playback speed.");
+    println!("2. Timelapse (one frame every N seconds).");
+    let (change, suffix) = match user_input::read_input().as_str() {
+        "1" => {
+            println!(
+                "Speed factor ({}x - {}x, e.g. 2 or 0.5):",
+                speed::MIN_FACTOR,
+                speed::MAX_FACTOR
+            );
+            let factor = speed::parse_factor(&user_input::read_input())
+                .ok_or(TranscoderError::InvalidCommand)?;
+            println!("Drop the audio (y/N)?");
+            let keep_audio = user_input::read_input().to_lowercase() != "y";
+            (SpeedChange::Factor { factor, keep_audio }, "_speed")
+        }
+        "2" => {
+            println!("Seconds between frames (e.g. 5):");
+            let interval = user_input::read_input()
+                .parse::<f64>()
+                .ok()
+                .filter(|interval| *interval > 0.0)
+                .ok_or(TranscoderError::InvalidCommand)?;
+            println!("Output frame rate (default 30):");
+            let frame_rate = user_input::read_optional::<u32>("frame rate")
+                .filter(|rate| *rate > 0)
+                .unwrap_or(30);
+            let change = SpeedChange::Timelapse {
+                interval,
+                frame_rate,
+            };
+            (change, "_timelapse")
+        }
+        _ => return Err(TranscoderError::UnsupportedOperation.into()),
+    };
+
+    let video_filters = ask_deinterlace(&input)?.into_iter().collect::<Vec<_>>();
+
+    let ext = string_utils::find_file_extension(&input)?;
+    let output = string_utils::change_file_extension(&input, &format!("{}{}", suffix, ext))?;
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::Speed(change))
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::Aac)
+        .video_codec(VideoCodec::Libx264)
+        .video_filters(video_filters)
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn reverse() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+    let info = probe::probe(&input)?;
+    let duration = info
+        .duration
+        .ok_or(TranscoderError::ProbeFailed(input.clone()))?;
+    if info.video_stream().is_none() {
+        return Err(TranscoderError::AbortTranscoding("No video stream found.".to_owned()).into());
+    }
+    let has_audio = info.streams_of_type("audio").next().is_some();
+
+    println!(
+        "Chunk length in seconds, shorter chunks use less memory (default {}):",
+        playback::DEFAULT_CHUNK_SECONDS
+    );
+    let chunk = user_input::read_optional::<f64>("chunk length")
+        .filter(|chunk| *chunk > 0.0)
+        .unwrap_or(playback::DEFAULT_CHUNK_SECONDS);
+    let video_filters = ask_deinterlace(&input)?.into_iter().collect::<Vec<_>>();
+    let chunks = playback::chunks(duration, chunk);
+    // Matroska holds whatever the parts are encoded with, the final step only copies the video
+    let parts = (1..=chunks.len())
+        .map(|i| string_utils::temp_file_path(&format!("reversed{}.mkv", i)))
+        .collect::<Vec<_>>();
+    let list_file = string_utils::temp_file_path("concat.txt");
+    let reversed_parts = parts.iter().rev().cloned().collect::<Vec<_>>();
+    std::fs::write(&list_file, cut_list::concat_list(&reversed_parts))?;
+    let mut temp_files = parts.clone();
+    temp_files.push(list_file.clone());
+
+    let mut audio_parts = Vec::new();
+    let mut audio_list_file = None;
+    if has_audio {
+        audio_parts = (1..=chunks.len())
+            .map(|i| string_utils::temp_file_path(&format!("reversed{}.wav", i)))
+            .collect::<Vec<_>>();
+        let list_file = string_utils::temp_file_path("audio_concat.txt");
+        let reversed_parts = audio_parts.iter().rev().cloned().collect::<Vec<_>>();
+        std::fs::write(&list_file, cut_list::concat_list(&reversed_parts))?;
+        temp_files.extend(audio_parts.iter().cloned());
+        temp_files.push(list_file.clone());
+        audio_list_file = Some(list_file);
+    }
+
+    let output = string_utils::change_file_extension(&input, "_reversed.mp4")?;
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::Reverse(ReverseOptions {
+            chunks,
+            parts,
+            list_file,
+            audio_parts,
+            audio_list_file,
+        }))
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::Aac)
+        .video_codec(VideoCodec::Libx264)
+        .video_filters(video_filters)
+        .temp_files(temp_files)
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn loop_clip() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+    let clip_duration = probe::probe(&input)?
+        .duration
+        .filter(|duration| *duration > 0.0)
+        .ok_or(TranscoderError::ProbeFailed(input.clone()))?;
+
+    println!("Chose a loop operation:");
+    println!("1. Repeat N times.");
+    println!("2. Repeat until a target duration.");
+    let (count, duration) = match user_input::read_input().as_str() {
+        "1" => {
+            println!("Number of repetitions (e.g. 3):");
+            let count = user_input::read_input()
+                .parse::<u32>()
+                .ok()
+                .filter(|count| *count > 0)
+                .ok_or(TranscoderError::InvalidCommand)?;
+            (count, None)
+        }
+        "2" => {
+            println!("Target duration (e.g. 01:00:00 or 3600):");
+            let target = string_utils::parse_timestamp(&user_input::read_input())
+                .ok()
+                .filter(|target| *target > 0.0)
+                .ok_or(TranscoderError::InvalidCommand)?;
+            let count = playback::loop_count(clip_duration, target).unwrap_or(u32::MAX);
+            (count, Some(target))
+        }
+        _ => return Err(TranscoderError::UnsupportedOperation.into()),
+    };
+    if count > playback::MAX_LOOPS {
+        return Err(TranscoderError::AbortTranscoding(format!(
+            "At most {} repetitions are supported.",
+            playback::MAX_LOOPS
+        ))
+        .into());
+    }
+
+    println!("Re-encode instead of stream copy, for sources the concat demuxer can't join (y/N)?");
+    let mut temp_files = Vec::new();
+    let mut video_filters = Vec::new();
+    let list_file = match user_input::read_input().to_lowercase() == "y" {
+        true => {
+            video_filters.extend(ask_deinterlace(&input)?);
+            None
+        }
+        false => {
+            // the concat demuxer resolves relative paths against the list file
+            let source = std::fs::canonicalize(&input)?
+                .to_string_lossy()
+                .into_owned();
+            let list_file = string_utils::temp_file_path("concat.txt");
+            std::fs::write(
+                &list_file,
+                cut_list::concat_list(&vec![source; count as usize]),
+            )?;
+            temp_files.push(list_file.clone());
+            Some(list_file)
+        }
+    };
+
+    let ext = string_utils::find_file_extension(&input)?;
+    let output = string_utils::change_file_extension(&input, &format!("_loop{}", ext))?;
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::Loop(LoopOptions {
+            count,
+            list_file,
+            duration,
+        }))
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::Aac)
+        .video_codec(VideoCodec::Libx264)
+        .video_filters(video_filters)
+        .temp_files(temp_files)
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn watermark() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+    let mut temp_files = Vec::new();
+    let watermark = ask_watermark_options(&mut temp_files)?;
+    let video_filters = ask_deinterlace(&input)?.into_iter().collect::<Vec<_>>();
+
+    let ext = string_utils::find_file_extension(&input)?;
+    let output = string_utils::change_file_extension(&input, &format!("_watermark{}", ext))?;
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::Filter)
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::Custom("copy".to_owned()))
+        .video_codec(VideoCodec::Libx264)
+        .video_filters(video_filters)
+        .overlays(vec![watermark.filter()])
+        .preset("medium".to_owned())
+        .crf(18)
+        .temp_files(temp_files)
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+pub fn text_overlays() -> Result<FfmpegCommand, Box<dyn Error>> {
+    let input = user_input::ask_input_file()?;
+    let info = probe::probe(&input)?;
+
+    let mut style = TextStyle::default();
+    println!("Font file (e.g. /usr/share/fonts/DejaVuSans.ttf, default font):");
+    style.font_file = Some(user_input::read_input()).filter(|f| !f.is_empty());
+    println!("Font size (default {}):", style.size);
+    if let Some(size) = user_input::read_optional::<u32>("font size") {
+        style.size = size;
+    }
+    style.color = ask_font_color(&style.color);
+    println!("Background box behind the text (Y/n)?");
+    style.background = user_input::read_input().to_lowercase() != "n";
+    let overlay = |content: OverlayContent, position: Position| TextOverlay {
+        content,
+        position,
+        margin: 20,
+        style: style.clone(),
+    };
+
+    let mut overlays = Vec::new();
+    println!("Burn in timecode (Y/n)?");
+    if user_input::read_input().to_lowercase() != "n" {
+        let frame_rate = info
+            .video_stream()
+            .and_then(|v| v.frame_rate)
+            .ok_or(TranscoderError::ProbeFailed(input.clone()))?;
+        let source = info
+            .timecode()
+            .and_then(|tc| text_overlay::parse_timecode(tc, frame_rate))
+            .unwrap_or("00:00:00:00".to_owned());
+        println!(
+            "Start timecode (HH:MM:SS:FF, HH:MM:SS;FF for drop frame at 29.97 or 59.94 fps, default {}):",
+            source
+        );
+        let value = user_input::read_input();
+        let start = match text_overlay::parse_timecode(&value, frame_rate) {
+            Some(start) => start,
+            None => {
+                if !value.is_empty() {
+                    eprintln!("Invalid timecode.");
+                }
+                source
+            }
+        };
+        let rate = format!("{}", (frame_rate * 1000.0).round() / 1000.0);
+        let content = OverlayContent::Timecode { start, rate };
+        overlays.push(overlay(content, ask_position(Position::Bottom)));
+    }
+    println!("Burn in the file name (y/N)?");
+    if user_input::read_input().to_lowercase() == "y" {
+        let name = std::path::Path::new(&input)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let content = OverlayContent::Literal(name);
+        overlays.push(overlay(content, ask_position(Position::TopLeft)));
+    }
+    loop {
+        println!(
+            "Text (e.g. DRAFT, frame %{{frame_num}}, %{{pts:hms}} or %{{localtime:%Y-%m-%d}}), leave blank to finish:"
+        );
+        let text = user_input::read_input();
+        if text.is_empty() {
+            break;
+        }
+        let content = OverlayContent::Text(text);
+        overlays.push(overlay(content, ask_position(Position::TopRight)));
+    }
+    if overlays.is_empty() {
+        return Err(TranscoderError::AbortTranscoding("Nothing to overlay.".to_owned()).into());
+    }
+    let video_filters = ask_deinterlace(&input)?.into_iter().collect::<Vec<_>>();
+
+    let ext = string_utils::find_file_extension(&input)?;
+    let output = string_utils::change_file_extension(&input, &format!("_overlay{}", ext))?;
+    let cmd = ffmpeg_command::builder()
+        .command_type(CommandType::Filter)
+        .input_file(input)
+        .output_file(output)
+        .audio_codec(AudioCodec::Custom("copy".to_owned()))
+        .video_codec(VideoCodec::Libx264)
+        .video_filters(video_filters)
+        .overlays(overlays.iter().map(|o| o.filter()).collect::<Vec<_>>())
+        .preset("medium".to_owned())
+        .crf(18)
+        .build();
+
+    unwrap_ffmpeg_command(cmd)
+}
+
+/// The policy is resolved against the probed size, or the crop when one is applied first.
+fn ask_scaling(input: &str, crop: Option<&Crop>) -> Result<Option<Scaling>, Box<dyn Error>> {
+    let info = probe::probe(input)?;
+    let Some(video) = info.video_stream() else {
+        return Ok(None);
+    };
+    let (Some(width), Some(height)) = (video.width, video.height) else {
+        return Err(TranscoderError::ProbeFailed(input.to_owned()).into());
+    };
+    let mut source = SourceSize {
+        width,
+        height,
+        sample_aspect_ratio: video.sample_aspect_ratio.unwrap_or(1.0),
+        rotation: video.display_rotation(),
+    };
+    // cropping happens after ffmpeg applied the rotation
+    if let Some(crop) = crop {
+        source.width = crop.width;
+        source.height = crop.height;
+        source.rotation = 0;
+    }
+
+    let mut policy = ScalePolicy::default();
+    println!(
+        "Fit within box (default {}x{}, turned for vertical video):",
+        policy.box_width, policy.box_height
+    );
+    let value = user_input::read_input();
+    match value.split_once('x').map(|(w, h)| (w.parse(), h.parse())) {
+        Some((Ok(w), Ok(h))) if w > 0 && h > 0 => {
+            policy.box_width = w;
+            policy.box_height = h;
+        }
+        _ if value.is_empty() => {}
+        _ => eprintln!("Invalid box."),
+    }
+    println!("Allow upscaling (y/N)?");
+    policy.upscale = user_input::read_input().to_lowercase() == "y";
+    println!("Scaling algorithm (lanczos/bicubic, default lanczos):");
+    let value = user_input::read_input();
+    match ScaleAlgorithm::parse(&value) {
+        Some(algorithm) => policy.algorithm = algorithm,
+        None if value.is_empty() => {}
+        None => eprintln!("Invalid algorithm."),
+    }
+    println!("Pad to the exact box size with a colour (e.g. black) or blur, leave blank for none:");
+    let value = user_input::read_input();
+    policy.padding = Padding::parse(&value);
+    if policy.padding.is_none() && !value.is_empty() {
+        eprintln!("Invalid padding.");
+    }
+
+    let scaling = Scaling { policy, source };
+    let (width, height) = scaling.output_size();
+    println!("Output size: {}x{}.", width, height);
+    Ok(Some(scaling))
+}
+
+/// Runs the interlace analysis unless a deinterlacer is picked by hand or turned off. The
+/// analysis is the default only when the container doesn't report progressive video, it decodes
+/// hundreds of frames. Every operation that re-encodes the whole video asks for it. Smart cut
+/// doesn't, its re-encoded GOPs are joined with copied ones and must keep the source's fields.
+fn ask_deinterlace(input: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let progressive = probe::probe(input)?
+        .video_stream()
+        .and_then(|v| v.field_order.as_deref())
+        == Some("progressive");
+    let default = if progressive { "off" } else { "auto" };
+    println!(
+        "Deinterlace (auto/off/bwdif/yadif/ivtc, default {}):",
+        default
+    );
+    let value = user_input::read_input().to_lowercase();
+    match value.as_str() {
+        "" if progressive => return Ok(None),
+        "" | "auto" => {}
+        "off" => return Ok(None),
+        value => {
+            let deinterlacer =
+                Deinterlacer::parse(value).ok_or(TranscoderError::UnsupportedOperation)?;
+            return Ok(Some(deinterlacer.filter(ScanType::Progressive.parity())));
+        }
+    }
+
+    println!("Checking for interlacing...");
+    let Some(scan) = interlace::detect(input) else {
+        println!("Could not analyze the video, it is left as is.");
+        return Ok(None);
+    };
+    let deinterlacer = Deinterlacer::for_scan(scan);
+    match deinterlacer {
+        Some(d) => println!("The video is {}, applying {:?}.", scan, d),
+        None => println!("The video is {}.", scan),
+    }
+    Ok(deinterlacer.map(|d| d.filter(scan.parity())))
+}
+
+fn ask_auto_crop(input: &str) -> Result<Option<Crop>, Box<dyn Error>> {
+    println!("Auto-crop black bars (y/N)?");
+    if user_input::read_input().to_lowercase() != "y" {
+        return Ok(None);
+    }
+    detect_and_confirm_crop(input)
+}
+
+fn detect_and_confirm_crop(input: &str) -> Result<Option<Crop>, Box<dyn Error>> {
+    let info = probe::probe(input)?;
+    let duration = info
+        .duration
+        .ok_or(TranscoderError::ProbeFailed(input.to_owned()))?;
+    let video = info
+        .video_stream()
+        .ok_or(TranscoderError::AbortTranscoding(
+            "No video stream found.".to_owned(),
+        ))?;
+
+    println!("Detecting black bars...");
+    let (detected, votes) = match crop::detect_crop(input, duration) {
+        Some(result) => result,
+        None => {
+            println!("Could not detect black bars.");
+            return Ok(None);
+        }
+    };
+    // cropdetect sees the frames after ffmpeg applied the rotation, still in stored pixels
+    let (Some(width), Some(height)) = (video.width, video.height) else {
+        return Err(TranscoderError::ProbeFailed(input.to_owned()).into());
+    };
+    let source = SourceSize {
+        width,
+        height,
+        sample_aspect_ratio: 1.0,
+        rotation: video.display_rotation(),
+    };
+    if source.display_size() == (detected.width as f64, detected.height as f64) {
+        println!("No black bars found.");
+        return Ok(None);
+    }
+
+    println!(
+        "Detected crop {} ({} of {} samples agree). Apply it (Y/n) or enter another one as w:h:x:y:",
+        detected,
+        votes,
+        crop::SAMPLE_COUNT
+    );
+    let answer = user_input::read_input();
+    match answer.to_lowercase().as_str() {
+        "" | "y" => Ok(Some(detected)),
+        "n" => Ok(None),
+        value => match Crop::parse(value) {
+            Some(crop) => Ok(Some(crop)),
+            None => {
+                eprintln!("Invalid crop.");
+                Ok(None)
+            }
+        },
+    }
+}
+
+fn ask_watermark(temp_files: &mut Vec<String>) -> Result<Option<Watermark>, Box<dyn Error>> {
+    println!("Add a watermark (y/N)?");
+    if user_input::read_input().to_lowercase() != "y" {
+        return Ok(None);
+    }
+    ask_watermark_options(temp_files).map(Some)
+}
+
+fn ask_watermark_options(temp_files: &mut Vec<String>) -> Result<Watermark, Box<dyn Error>> {
+    println!("Chose a watermark type:");
+    println!("1. Image (PNG).");
+    println!("2. Text.");
+    let choice = user_input::read_input();
+    if !["1", "2"].contains(&choice.as_str()) {
+        return Err(TranscoderError::UnsupportedOperation.into());
+    }
+
+    let image = match choice.as_str() {
+        "1" => Some(user_input::ask_existing_file(
+            "Provide image path (e.g. /some/directory/logo.png):",
+        )?),
+        _ => None,
+    };
+    let text = match image {
+        Some(_) => String::new(),
+        None => {
+            println!("Watermark text:");
+            let text = user_input::read_input();
+            if text.is_empty() {
+                return Err(TranscoderError::InvalidCommand.into());
+            }
+            text
+        }
+    };
+
+    println!(
+        "You will be asked a few optional parameters. Leave the input blank to use the default."
+    );
+    let position = ask_position(Position::default());
+    println!("Margin in pixels (default 20):");
+    let margin = user_input::read_optional::<u32>("margin").unwrap_or(20);
+
+    let watermark = match image {
+        Some(path) => {
+            println!("Width relative to the video width (default 0.15):");
+            let scale = user_input::read_optional::<f64>("width")
+                .filter(|scale| *scale > 0.0 && *scale <= 1.0)
+                .unwrap_or(0.15);
+            println!("Opacity from 0 to 1 (default 1):");
+            let opacity = user_input::read_optional::<f64>("opacity")
+                .filter(|opacity| (0.0..=1.0).contains(opacity))
+                .unwrap_or(1.0);
+            Watermark::Image {
+                path,
+                position,
+                margin,
+                scale,
+                opacity,
+            }
+        }
+        None => {
+            println!("Font file (e.g. /usr/share/fonts/DejaVuSans.ttf, default font):");
+            let font_file = Some(user_input::read_input()).filter(|f| !f.is_empty());
+            println!("Font size (default 32):");
+            let size = user_input::read_optional::<u32>("font size").unwrap_or(32);
+            let color = ask_font_color("white@0.7");
+            let text_file = string_utils::temp_file_path("watermark.txt");
+            std::fs::write(&text_file, text)?;
+            temp_files.push(text_file.clone());
+            Watermark::Text {
+                text_file,
+                font_file,
+                size,
+                color,
+                position,
+                margin,
+            }
+        }
+    };
+    Ok(watermark)
+}
+
+fn ask_position(default: Position) -> Position {
+    println!(
+        "Position (top-left/top/top-right/bottom-left/bottom/bottom-right/center, default {}):",
+        default.as_str()
+    );
+    let value = user_input::read_input();
+    match Position::parse(&value) {
+        Some(position) => position,
+        None => {
+            if !value.is_empty() {
+                eprintln!("Invalid position.");
+            }
+            default
+        }
+    }
+}
+
+fn ask_font_color(default: &str) -> String {
+    println!(
+        "Font colour (e.g. white or white@0.5, default {}):",
+        default
+    );
+    let value = user_input::read_input();
+    let valid = value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "#@.".contains(c));
+    if !valid {
+        eprintln!("Invalid colour.");
+    }
+    match value.is_empty() || !valid {
+        true => default.to_owned(),
+        false => value,
+    }
+}
+
+fn ask_audio_settings(cmd: &mut FfmpegCommandBuilder, audio_codec: &str) {
+    println!("Audio sample rate (e.g. 48000):");
+    if let Some(sample_rate) = user_input::read_optional::<u32>("sample rate") {
+        cmd.sample_rate(sample_rate);
+    }
+
+    println!("Audio channels (e.g. 2 or a layout like 5.1):");
+    let value = user_input::read_input();
+    if let Ok(count) = value.parse::<u8>() {
+        cmd.audio_channels(count);
+    } else if let Some(layout) = channels::layout_channels(&value) {
+        cmd.channel_layout(value.clone());
+        cmd.audio_channels(layout.len() as u8);
+    } else if !value.is_empty() {
+        eprintln!("Invalid channels.");
+    }
+
+    let audio_codec = audio_codec.to_lowercase();
+    if audio_codec.starts_with("pcm_") || audio_codec == "flac" {
+        println!("Sample format or bit depth (16, 24, 32 or f32):");
+        let value = user_input::read_input();
+        match SampleFormat::parse(&value).filter(|f| f.supported_by(&audio_codec)) {
+            Some(format) => {
+                cmd.sample_format(format);
+            }
+            None if !value.is_empty() => eprintln!("Invalid sample format."),
+            None => {}
+        }
+    }
+
+    println!("Resampler (swr or soxr):");
+    let engine = user_input::read_input();
+    if !engine.is_empty() {
+        println!("Dither method (e.g. triangular, shibata), leave blank for none:");
+        match Resampler::parse(&engine, &user_input::read_input()) {
+            Some(resampler) => {
+                cmd.resampler(resampler);
+            }
+            None => eprintln!("Invalid resampler."),
+        }
+    }
+}
+
+fn ask_ducking_options(input: &str) -> Result<DuckingOptions, Box<dyn Error>> {
+    let mut ducking = DuckingOptions {
+        duration: probe_duration(input)?,
+        ..Default::default()
+    };
+    println!("Customize ducking (y/N)?");
+    if user_input::read_input().to_lowercase() == "y" {
+        println!(
+            "Threshold (default {}, lower ducks on quieter speech):",
+            ducking.threshold
+        );
+        ducking.threshold = user_input::read_optional("threshold").unwrap_or(ducking.threshold);
+        println!("Ratio (default {}):", ducking.ratio);
+        ducking.ratio = user_input::read_optional("ratio").unwrap_or(ducking.ratio);
+        println!("Attack in milliseconds (default {}):", ducking.attack_ms);
+        ducking.attack_ms = user_input::read_optional("attack").unwrap_or(ducking.attack_ms);
+        println!("Release in milliseconds (default {}):", ducking.release_ms);
+        ducking.release_ms = user_input::read_optional("release").unwrap_or(ducking.release_ms);
+    }
+    println!("Music fade out in seconds (default {}):", ducking.fade_out);
+    ducking.fade_out = user_input::read_optional("fade out").unwrap_or(ducking.fade_out);
+    Ok(ducking)
+}
+
+fn ask_stream_mapping(input: &str) -> Result<Option<StreamMapping>, Box<dyn Error>> {
+    println!("Customize stream mapping (y/N)?");
+    if user_input::read_input().to_lowercase() != "y" {
+        return Ok(None);
+    }
+
+    let info = probe::probe(input)?;
+    println!("Streams found in the file:");
+    for stream in info.streams.iter() {
+        println!("{}", stream.describe());
+    }
+
+    println!("Streams to keep (all, languages e.g. eng,pol or indices e.g. 0,1,3):");
+    let selection = match StreamSelection::parse(&user_input::read_input()) {
+        Some(StreamSelection::Indices(indices))
+            if indices
+                .iter()
+                .any(|i| !info.streams.iter().any(|s| s.index == *i)) =>
+        {
+            eprintln!("Invalid stream index.");
+            return Ok(None);
+        }
+        Some(selection) => selection,
+        None => {
+            eprintln!("Invalid stream selection.");
+            return Ok(None);
+        }
+    };
+
+    let (mut drop_subtitles, mut drop_data) = (false, false);
+    if !matches!(selection, StreamSelection::Indices(_)) {
+        println!("Drop subtitle streams (y/N)?");
+        drop_subtitles = user_input::read_input().to_lowercase() == "y";
+        println!("Drop data streams (y/N)?");
+        drop_data = user_input::read_input().to_lowercase() == "y";
+    }
+
+    Ok(Some(StreamMapping {
+        selection,
+        drop_subtitles,
+        drop_data,
+    }))
+}
+
+fn probe_duration(input: &str) -> Result<f64, TranscoderError> {
+    probe::probe(input)?
+        .duration
+        .filter(|duration| *duration > 0.0)
+        .ok_or(TranscoderError::ProbeFailed(input.to_owned()))
+}
+
+fn file_info_header(input: &str, info: &probe::MediaInfo) -> String {
+    let name = std::path::Path::new(input)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut header = format!(
+        "{}  |  {}",
+        name,
+        string_utils::format_timestamp(info.duration.unwrap_or_default())
+    );
+    if let Some(video) = info.video_stream() {
+        if let (Some(w), Some(h)) = (video.width, video.height) {
+            header.push_str(format!("  |  {}x{} {}", w, h, video.codec_name).as_str());
+        }
+    }
+    if let Some(size) = info.size {
+        header.push_str(format!("  |  {:.1} MB", size as f64 / 1_048_576.0).as_str());
+    }
+    header.replace('\\', "\\\\").replace('%', "\\%")
+}
+
 #[inline(always)]
 fn unwrap_ffmpeg_command(
     cmd: Result<FfmpegCommand, FfmpegCommandBuilderError>,
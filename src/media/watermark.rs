@@ -0,0 +1,176 @@
+use crate::string_utils;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Position {
+    TopLeft,
+    Top,
+    TopRight,
+    BottomLeft,
+    Bottom,
+    #[default]
+    BottomRight,
+    Center,
+}
+
+impl Position {
+    pub fn parse(value: &str) -> Option<Position> {
+        match value.to_lowercase().replace(' ', "-").as_str() {
+            "top-left" | "tl" => Some(Position::TopLeft),
+            "top" | "t" => Some(Position::Top),
+            "top-right" | "tr" => Some(Position::TopRight),
+            "bottom-left" | "bl" => Some(Position::BottomLeft),
+            "bottom" | "b" => Some(Position::Bottom),
+            "bottom-right" | "br" => Some(Position::BottomRight),
+            "center" | "c" => Some(Position::Center),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Position::TopLeft => "top-left",
+            Position::Top => "top",
+            Position::TopRight => "top-right",
+            Position::BottomLeft => "bottom-left",
+            Position::Bottom => "bottom",
+            Position::BottomRight => "bottom-right",
+            Position::Center => "center",
+        }
+    }
+
+    /// `x` and `y` expressions for an element of size `width` x `height` (variable names
+    /// of the filter) on the main picture `W` x `H`.
+    pub fn coordinates(&self, margin: u32, width: &str, height: &str) -> (String, String) {
+        let left = margin.to_string();
+        let right = format!("W-{}-{}", width, margin);
+        let top = margin.to_string();
+        let bottom = format!("H-{}-{}", height, margin);
+        let center = format!("(W-{})/2", width);
+        match self {
+            Position::TopLeft => (left, top),
+            Position::Top => (center, top),
+            Position::TopRight => (right, top),
+            Position::BottomLeft => (left, bottom),
+            Position::Bottom => (center, bottom),
+            Position::BottomRight => (right, bottom),
+            Position::Center => (center, format!("(H-{})/2", height)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Watermark {
+    Image {
+        path: String,
+        position: Position,
+        margin: u32,
+        /// Logo width as a share of the video width.
+        scale: f64,
+        opacity: f64,
+    },
+    Text {
+        /// The text is read from a file, so it needs no escaping.
+        text_file: String,
+        font_file: Option<String>,
+        size: u32,
+        color: String,
+        position: Position,
+        margin: u32,
+    },
+}
+
+impl Watermark {
+    /// Goes last in the `-vf` chain. The image variant labels the picture so far as `[main]`,
+    /// loads the logo with `movie` and scales it against the main picture with `scale2ref`.
+    pub fn filter(&self) -> String {
+        match self {
+            Watermark::Image {
+                path,
+                position,
+                margin,
+                scale,
+                opacity,
+            } => {
+                let (x, y) = position.coordinates(*margin, "w", "h");
+                let alpha = match *opacity < 1.0 {
+                    true => format!(",colorchannelmixer=aa={}", opacity),
+                    false => String::new(),
+                };
+                format!(
+                    "null[main];movie={},format=rgba{}[logo];[logo][main]scale2ref=w=main_w*{}:h=ow/a[logo][main];[main][logo]overlay=x={}:y={}",
+                    string_utils::escape_filter_value(path),
+                    alpha,
+                    scale,
+                    x,
+                    y
+                )
+            }
+            Watermark::Text {
+                text_file,
+                font_file,
+                size,
+                color,
+                position,
+                margin,
+            } => {
+                let (x, y) = position.coordinates(*margin, "tw", "th");
+                let font = font_file
+                    .as_ref()
+                    .map(|f| format!(":fontfile={}", string_utils::escape_filter_value(f)))
+                    .unwrap_or_default();
+                format!(
+                    "drawtext=textfile={}:expansion=none{}:fontsize={}:fontcolor={}:x={}:y={}",
+                    string_utils::escape_filter_value(text_file),
+                    font,
+                    size,
+                    color,
+                    x,
+                    y
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_image_watermark_filter() {
+        let watermark = Watermark::Image {
+            path: "/logos/logo.png".to_owned(),
+            position: Position::BottomRight,
+            margin: 20,
+            scale: 0.15,
+            opacity: 0.8,
+        };
+        assert_eq!(
+            watermark.filter(),
+            "null[main];movie=/logos/logo.png,format=rgba,colorchannelmixer=aa=0.8[logo];[logo][main]scale2ref=w=main_w*0.15:h=ow/a[logo][main];[main][logo]overlay=x=W-w-20:y=H-h-20"
+        );
+    }
+
+    #[test]
+    fn should_build_text_watermark_filter() {
+        let watermark = Watermark::Text {
+            text_file: "/tmp/watermark.txt".to_owned(),
+            font_file: Some("/fonts/Inter.ttf".to_owned()),
+            size: 32,
+            color: "white@0.6".to_owned(),
+            position: Position::TopLeft,
+            margin: 10,
+        };
+        assert_eq!(
+            watermark.filter(),
+            "drawtext=textfile=/tmp/watermark.txt:expansion=none:fontfile=/fonts/Inter.ttf:fontsize=32:fontcolor=white@0.6:x=10:y=10"
+        );
+    }
+
+    #[test]
+    fn should_parse_position() {
+        assert_eq!(Position::parse("top right"), Some(Position::TopRight));
+        assert_eq!(Position::parse("BL"), Some(Position::BottomLeft));
+        assert_eq!(Position::parse("middle"), None);
+    }
+}
@@ -34,7 +34,7 @@ pub fn add_quotes_if_whitespace_present(input: &str, output: &str) -> (String, S
 }
 
 #[inline(always)]
-fn wrap_with_quotes_if_whitespace(s: &str) -> String {
+pub fn wrap_with_quotes_if_whitespace(s: &str) -> String {
     if s.contains(' ') {
         let mut with_quotes = String::from("\"");
         with_quotes.push_str(s);
@@ -45,6 +45,124 @@ fn wrap_with_quotes_if_whitespace(s: &str) -> String {
     }
 }
 
+/// Wraps a value in double quotes for the shell, escaping the characters the shell still
+/// expands inside them.
+pub fn quote_value(value: &str) -> String {
+    let escaped = match cfg!(target_os = "windows") {
+        true => escape_chars(value, &['\\', '"']),
+        false => escape_chars(value, &['\\', '"', '$', '`']),
+    };
+    format!("\"{}\"", escaped)
+}
+
+/// Escapes a filter option value, e.g. a path or a drawtext text, for the filter option
+/// parser, then for the filtergraph parser and finally for the double quoted shell argument.
+/// The result is used unquoted.
+pub fn escape_filter_value(value: &str) -> String {
+    let value = escape_chars(value, &['\\', '\'', ':']);
+    let value = escape_chars(&value, &['\\', '\'', '[', ']', ',', ';']);
+    match cfg!(target_os = "windows") {
+        true => value.replace('"', "\\\""),
+        false => escape_chars(&value, &['\\', '"', '$', '`']),
+    }
+}
+
+fn escape_chars(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+pub fn parse_timestamp(timestamp: &str) -> Result<f64, &'static str> {
+    let mut seconds = 0.0;
+    let parts = timestamp.trim().split(':').collect::<Vec<_>>();
+    if parts.len() > 3 {
+        return Err("Invalid timestamp.");
+    }
+    for part in parts {
+        match part.parse::<f64>() {
+            Ok(v) if v >= 0.0 => seconds = seconds * 60.0 + v,
+            _ => return Err("Invalid timestamp."),
+        }
+    }
+    Ok(seconds)
+}
+
+/// SMPTE timecode, `HH:MM:SS:FF` with `;` or `.` before the frames for drop frame timecode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timecode {
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub frames: u32,
+    pub drop_frame: bool,
+}
+
+impl Timecode {
+    pub fn parse(value: &str) -> Option<Timecode> {
+        let value = value.trim();
+        let (time, frames) = value.rsplit_once([':', ';', '.'])?;
+        let parts = time
+            .split(':')
+            .chain(std::iter::once(frames))
+            .map(
+                |part| match part.len() == 2 && part.chars().all(|c| c.is_ascii_digit()) {
+                    true => part.parse::<u32>().ok(),
+                    false => None,
+                },
+            )
+            .collect::<Option<Vec<_>>>()?;
+        match parts.as_slice() {
+            [hours, minutes, seconds, frames] => Some(Timecode {
+                hours: *hours,
+                minutes: *minutes,
+                seconds: *seconds,
+                frames: *frames,
+                drop_frame: !value[time.len()..].starts_with(':'),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Frames are counted at the nominal rate, e.g. 30 for 29.97 fps, so the timecode runs slower
+    /// than the clock. Drop frame timecode makes up for it by skipping the first two frame
+    /// numbers (four at 59.94) of every minute except every tenth one.
+    pub fn to_seconds(self, fps: f64) -> f64 {
+        let nominal = fps.round() as u64;
+        let total_minutes = (self.hours * 60 + self.minutes) as u64;
+        let mut frames = (total_minutes * 60 + self.seconds as u64) * nominal + self.frames as u64;
+        if self.drop_frame {
+            let dropped = nominal / 30 * 2;
+            frames -= dropped * (total_minutes - total_minutes / 10);
+        }
+        frames as f64 / fps
+    }
+}
+
+pub fn format_timestamp(seconds: f64) -> String {
+    let millis = (seconds * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+pub fn temp_file_path(name: &str) -> String {
+    let file = format!("ffmpeg-cli-{}-{}", std::process::id(), name);
+    std::env::temp_dir()
+        .join(file)
+        .to_string_lossy()
+        .into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,4 +201,59 @@ mod tests {
         let result = wrap_with_quotes_if_whitespace(input);
         assert_eq!(result, input);
     }
+
+    #[test]
+    fn should_quote_value() {
+        assert_eq!(quote_value("My title"), r#""My title""#);
+        assert_eq!(quote_value(r#"The "best" one"#), r#""The \"best\" one""#);
+        if !cfg!(target_os = "windows") {
+            assert_eq!(quote_value("$HOME `id`"), r#""\$HOME \`id\`""#);
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn should_escape_filter_value() {
+        assert_eq!(escape_filter_value("/tmp/subs.srt"), "/tmp/subs.srt");
+        // option level: \'  \:  then filtergraph level: \\\'  \\:  \,  then shell: every \ doubled
+        assert_eq!(escape_filter_value("it's: a, b"), r"it\\\\\\'s\\\\: a\\, b");
+        assert_eq!(escape_filter_value(r#"say "$HOME""#), r#"say \"\$HOME\""#);
+        assert_eq!(
+            escape_filter_value(r"C:\Users\me\subs.srt"),
+            r"C\\\\:\\\\\\\\Users\\\\\\\\me\\\\\\\\subs.srt"
+        );
+    }
+
+    #[test]
+    fn should_parse_timestamps() {
+        assert_eq!(parse_timestamp("12.5").unwrap(), 12.5);
+        assert_eq!(parse_timestamp("01:30").unwrap(), 90.0);
+        assert_eq!(parse_timestamp("01:02:03.250").unwrap(), 3723.25);
+        assert!(parse_timestamp("1:2:3:4").is_err());
+        assert!(parse_timestamp("abc").is_err());
+        assert!(parse_timestamp("-5").is_err());
+    }
+
+    #[test]
+    fn should_parse_timecode() {
+        let ntsc = 30000.0 / 1001.0;
+        let timecode = Timecode::parse("00:00:01:12").unwrap();
+        assert!(!timecode.drop_frame);
+        assert_eq!(timecode.to_seconds(25.0), 1.48);
+        let timecode = Timecode::parse("01:00:00:00").unwrap();
+        assert_eq!(timecode.to_seconds(ntsc), 3603.6);
+        let timecode = Timecode::parse("00:10:00;00").unwrap();
+        assert!(timecode.drop_frame);
+        assert!((timecode.to_seconds(ntsc) - 600.0).abs() < 0.001);
+        let timecode = Timecode::parse("01:00:00.00").unwrap();
+        assert!((timecode.to_seconds(ntsc) - 3600.0).abs() < 0.01);
+        assert_eq!(Timecode::parse("1:00:00:00"), None);
+        assert_eq!(Timecode::parse("01:00:00"), None);
+    }
+
+    #[test]
+    fn should_format_timestamp() {
+        assert_eq!(format_timestamp(3723.25), "01:02:03.250");
+        assert_eq!(format_timestamp(0.0), "00:00:00.000");
+    }
 }
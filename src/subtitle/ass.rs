@@ -0,0 +1,131 @@
+use crate::error::SubtitleError;
+use crate::subtitle::{parse_time, Cue, SubtitleDocument};
+
+const EVENTS_SECTION: &str = "[Events]";
+const EVENTS_FORMAT: &str =
+    "Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text";
+const DEFAULT_HEADER: &str = "[Script Info]
+ScriptType: v4.00+
+PlayResX: 384
+PlayResY: 288
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,16,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,1,0,2,10,10,10,1
+";
+
+pub fn parse(content: &str) -> Result<SubtitleDocument, SubtitleError> {
+    let mut doc = SubtitleDocument::default();
+    let mut header = String::new();
+    let mut in_events = false;
+    let mut fields: Vec<String> = Vec::new();
+
+    for (n, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if !in_events {
+            if trimmed.eq_ignore_ascii_case(EVENTS_SECTION) {
+                in_events = true;
+            } else {
+                header.push_str(line);
+                header.push('\n');
+            }
+            continue;
+        }
+        if let Some(format) = trimmed.strip_prefix("Format:") {
+            fields = format.split(',').map(|f| f.trim().to_lowercase()).collect();
+        } else if let Some(dialogue) = trimmed.strip_prefix("Dialogue:") {
+            if fields.is_empty() {
+                return Err(SubtitleError::MissingEventFormat);
+            }
+            // the text is the last field and may itself contain commas
+            let values = dialogue
+                .trim()
+                .splitn(fields.len(), ',')
+                .collect::<Vec<_>>();
+            let field = |name: &str| {
+                fields
+                    .iter()
+                    .position(|f| f == name)
+                    .and_then(|i| values.get(i))
+                    .copied()
+            };
+            let time = |name: &str| {
+                field(name)
+                    .and_then(|t| parse_time(t, &['.']))
+                    .ok_or(SubtitleError::InvalidTimestamp(n + 1))
+            };
+            doc.cues.push(Cue {
+                start: time("start")?,
+                end: time("end")?,
+                text: field("text").unwrap_or_default().replace("\\N", "\n"),
+                style: field("style").map(|s| s.to_owned()),
+            });
+        }
+    }
+
+    if !header.trim().is_empty() {
+        doc.ass_header = Some(header);
+    }
+    Ok(doc)
+}
+
+pub fn write(doc: &SubtitleDocument) -> String {
+    let mut out = doc
+        .ass_header
+        .clone()
+        .unwrap_or_else(|| DEFAULT_HEADER.to_owned());
+    if !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+    out.push_str(EVENTS_SECTION);
+    out.push('\n');
+    out.push_str(EVENTS_FORMAT);
+    out.push('\n');
+    for cue in doc.cues.iter() {
+        out.push_str(
+            format!(
+                "Dialogue: 0,{},{},{},,0,0,0,,{}\n",
+                format_time(cue.start),
+                format_time(cue.end),
+                cue.style.as_deref().unwrap_or("Default"),
+                cue.text.replace('\n', "\\N")
+            )
+            .as_str(),
+        );
+    }
+    out
+}
+
+/// ASS uses `H:MM:SS.cc` with centisecond precision.
+fn format_time(ms: i64) -> String {
+    let cs = (ms.max(0) + 5) / 10;
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        cs / 360_000,
+        cs / 6000 % 60,
+        cs / 100 % 60,
+        cs % 100
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_and_write_ass() {
+        let ass = "[Script Info]\nTitle: test\n\n[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\nDialogue: 0,0:00:01.50,0:00:03.00,Main,,0,0,0,,Hello, {\\i1}world{\\i0}\\Nbye\n";
+        let doc = parse(ass).unwrap();
+        assert_eq!(doc.cues.len(), 1);
+        assert_eq!(doc.cues[0].start, 1_500);
+        assert_eq!(doc.cues[0].end, 3_000);
+        assert_eq!(doc.cues[0].text, "Hello, {\\i1}world{\\i0}\nbye");
+        assert_eq!(doc.cues[0].style.as_deref(), Some("Main"));
+
+        let written = write(&doc);
+        assert!(written.starts_with("[Script Info]\nTitle: test\n\n[Events]\n"));
+        assert!(written.ends_with(
+            "Dialogue: 0,0:00:01.50,0:00:03.00,Main,,0,0,0,,Hello, {\\i1}world{\\i0}\\Nbye\n"
+        ));
+    }
+}
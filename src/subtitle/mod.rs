@@ -0,0 +1,179 @@
+pub mod ass;
+pub mod srt;
+pub mod subtitle_tool;
+pub mod vtt;
+
+use crate::error::SubtitleError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubtitleFormat {
+    Srt,
+    WebVtt,
+    Ass,
+}
+
+impl SubtitleFormat {
+    pub fn from_path(path: &str) -> Result<SubtitleFormat, SubtitleError> {
+        let lower = path.to_lowercase();
+        if lower.ends_with(".srt") {
+            Ok(SubtitleFormat::Srt)
+        } else if lower.ends_with(".vtt") {
+            Ok(SubtitleFormat::WebVtt)
+        } else if lower.ends_with(".ass") || lower.ends_with(".ssa") {
+            Ok(SubtitleFormat::Ass)
+        } else {
+            Err(SubtitleError::UnsupportedFormat(path.to_owned()))
+        }
+    }
+}
+
+/// A single subtitle cue. Times are in milliseconds, lines of text are separated by '\n'.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub start: i64,
+    pub end: i64,
+    pub text: String,
+    pub style: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SubtitleDocument {
+    pub cues: Vec<Cue>,
+    /// Script info and styles of the source ASS file, kept so ASS -> ASS round trips preserve them.
+    pub ass_header: Option<String>,
+}
+
+impl SubtitleDocument {
+    pub fn parse(
+        content: &str,
+        format: &SubtitleFormat,
+    ) -> Result<SubtitleDocument, SubtitleError> {
+        let content = content.trim_start_matches('\u{feff}');
+        match format {
+            SubtitleFormat::Srt => srt::parse(content),
+            SubtitleFormat::WebVtt => vtt::parse(content),
+            SubtitleFormat::Ass => ass::parse(content),
+        }
+    }
+
+    pub fn write(&self, format: &SubtitleFormat) -> String {
+        match format {
+            SubtitleFormat::Srt => srt::write(self),
+            SubtitleFormat::WebVtt => vtt::write(self),
+            SubtitleFormat::Ass => ass::write(self),
+        }
+    }
+
+    /// Moves every cue by the offset. Cues that end up entirely before zero are dropped.
+    pub fn shift(&mut self, offset_ms: i64) {
+        for cue in self.cues.iter_mut() {
+            cue.start = (cue.start + offset_ms).max(0);
+            cue.end += offset_ms;
+        }
+        self.cues.retain(|cue| cue.end > 0);
+    }
+
+    /// Rescales timings for a video played at a different frame rate (e.g. 23.976 -> 25).
+    pub fn rescale(&mut self, from_fps: f64, to_fps: f64) {
+        let factor = from_fps / to_fps;
+        for cue in self.cues.iter_mut() {
+            cue.start = (cue.start as f64 * factor).round() as i64;
+            cue.end = (cue.end as f64 * factor).round() as i64;
+        }
+    }
+}
+
+/// Parses `HH:MM:SS<sep>fff` (hours optional), where the fraction may have any number of digits
+/// and `<sep>` is any of `separators`.
+pub fn parse_time(time: &str, separators: &[char]) -> Option<i64> {
+    let (clock, fraction) = time.trim().split_once(separators)?;
+    let mut ms = 0;
+    for part in clock.split(':') {
+        ms = ms * 60 + part.parse::<i64>().ok()?;
+    }
+    let digits = fraction.len() as u32;
+    let fraction = fraction.parse::<i64>().ok()?;
+    let fraction_ms = match digits {
+        0..=3 => fraction * 10_i64.pow(3 - digits),
+        _ => fraction / 10_i64.pow(digits - 3),
+    };
+    Some(ms * 1000 + fraction_ms)
+}
+
+pub fn format_time(ms: i64, separator: char) -> String {
+    let ms = ms.max(0);
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+/// Removes ASS override blocks such as `{\i1}` from the text.
+pub fn strip_override_tags(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '{' => in_tag = true,
+            '}' if in_tag => in_tag = false,
+            _ if !in_tag => result.push(c),
+            _ => {}
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cue(start: i64, end: i64) -> Cue {
+        Cue {
+            start,
+            end,
+            text: "text".to_owned(),
+            style: None,
+        }
+    }
+
+    #[test]
+    fn should_parse_and_format_time() {
+        assert_eq!(parse_time("01:02:03,450", &[',']), Some(3_723_450));
+        assert_eq!(parse_time("02:03.5", &['.']), Some(123_500));
+        assert_eq!(parse_time("0:00:01.25", &['.']), Some(1_250));
+        assert_eq!(parse_time("bad", &[',']), None);
+        assert_eq!(format_time(3_723_450, ','), "01:02:03,450");
+    }
+
+    #[test]
+    fn should_shift_and_drop_cues() {
+        let mut doc = SubtitleDocument {
+            cues: vec![cue(500, 1_500), cue(3_000, 4_000)],
+            ass_header: None,
+        };
+        doc.shift(-2_000);
+        assert_eq!(doc.cues, vec![cue(1_000, 2_000)]);
+
+        doc.shift(-1_500);
+        assert_eq!(doc.cues, vec![cue(0, 500)]);
+    }
+
+    #[test]
+    fn should_rescale_between_frame_rates() {
+        let mut doc = SubtitleDocument {
+            cues: vec![cue(25_000, 50_000)],
+            ass_header: None,
+        };
+        doc.rescale(25.0, 23.976);
+        assert_eq!(doc.cues, vec![cue(26_068, 52_135)]);
+    }
+
+    #[test]
+    fn should_strip_override_tags() {
+        assert_eq!(strip_override_tags(r"{\i1}Hello{\i0} world"), "Hello world");
+    }
+}
@@ -0,0 +1,79 @@
+use crate::error::SubtitleError;
+use crate::subtitle::{format_time, parse_time, strip_override_tags, Cue, SubtitleDocument};
+
+pub fn parse(content: &str) -> Result<SubtitleDocument, SubtitleError> {
+    let mut doc = SubtitleDocument::default();
+    let mut lines = content.lines().enumerate().peekable();
+
+    while let Some((n, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || !line.contains("-->") {
+            continue;
+        }
+        let (start, end) = line
+            .split_once("-->")
+            .ok_or(SubtitleError::InvalidTimestamp(n + 1))?;
+        // many web tools write `.` instead of `,` before the milliseconds
+        let start = parse_time(start, &[',', '.']).ok_or(SubtitleError::InvalidTimestamp(n + 1))?;
+        let end = parse_time(end, &[',', '.']).ok_or(SubtitleError::InvalidTimestamp(n + 1))?;
+
+        let mut text = Vec::new();
+        while let Some((_, line)) = lines.next_if(|(_, l)| !l.trim().is_empty()) {
+            text.push(line.trim_end());
+        }
+        doc.cues.push(Cue {
+            start,
+            end,
+            text: text.join("\n"),
+            style: None,
+        });
+    }
+    Ok(doc)
+}
+
+pub fn write(doc: &SubtitleDocument) -> String {
+    let mut out = String::new();
+    for (i, cue) in doc.cues.iter().enumerate() {
+        out.push_str(
+            format!(
+                "{}\n{} --> {}\n{}\n\n",
+                i + 1,
+                format_time(cue.start, ','),
+                format_time(cue.end, ','),
+                strip_override_tags(&cue.text)
+            )
+            .as_str(),
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRT: &str = "1\n00:00:01,000 --> 00:00:02,500\nHello\nworld\n\n2\n00:00:03,000 --> 00:00:04,000\nBye\n\n";
+
+    #[test]
+    fn should_parse_and_write_srt() {
+        let doc = parse(SRT).unwrap();
+        assert_eq!(doc.cues.len(), 2);
+        assert_eq!(doc.cues[0].start, 1_000);
+        assert_eq!(doc.cues[0].end, 2_500);
+        assert_eq!(doc.cues[0].text, "Hello\nworld");
+
+        assert_eq!(write(&doc), SRT);
+    }
+
+    #[test]
+    fn should_accept_dot_before_milliseconds() {
+        let doc = parse("1\n00:00:01.000 --> 00:00:02.500\nHello\n").unwrap();
+        assert_eq!(doc.cues[0].end, 2_500);
+        assert_eq!(write(&doc), "1\n00:00:01,000 --> 00:00:02,500\nHello\n\n");
+    }
+
+    #[test]
+    fn should_fail_on_invalid_timestamp() {
+        assert!(parse("1\n00:00:01 --> 00:00:02,000\nHello\n").is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::error::TranscoderError;
+use crate::subtitle::{SubtitleDocument, SubtitleFormat};
+use crate::{string_utils, user_input};
+use log::debug;
+use std::error::Error;
+
+pub const USAGE: &str =
+    "Usage: ffmpeg-cli subtitles <input> <output> [--shift <ms>] [--fps <from>:<to>]";
+
+#[derive(Debug, Default)]
+pub struct SubtitleEdit {
+    pub input: String,
+    pub output: String,
+    pub shift_ms: Option<i64>,
+    pub fps: Option<(f64, f64)>,
+}
+
+pub fn run_interactive() -> Result<(), Box<dyn Error>> {
+    let input = user_input::ask_existing_file("Provide subtitle path (srt/vtt/ass):")?;
+    SubtitleFormat::from_path(&input)?;
+
+    println!("Output format (srt/vtt/ass), leave blank to keep the current one:");
+    let format = user_input::read_input();
+    let output = match format.is_empty() {
+        true => {
+            let ext = string_utils::find_file_extension(&input)?;
+            string_utils::change_file_extension(&input, &format!("_fixed{}", ext))?
+        }
+        false => string_utils::change_file_extension(&input, &format!("_fixed.{}", format))?,
+    };
+
+    println!(
+        "You will be asked a few optional parameters. Leave the input blank to skip any of them."
+    );
+    println!("Shift in milliseconds (e.g. -1500):");
+    let shift_ms = user_input::read_optional("shift");
+    println!("Frame rate conversion (e.g. 23.976:25):");
+    let fps = parse_fps(&user_input::read_input());
+
+    let edit = SubtitleEdit {
+        input,
+        output,
+        shift_ms,
+        fps,
+    };
+    apply(&edit)
+}
+
+pub fn run_subcommand(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut args = args.iter();
+    let (Some(input), Some(output)) = (args.next(), args.next()) else {
+        return Err(usage_error().into());
+    };
+    let mut edit = SubtitleEdit {
+        input: input.to_owned(),
+        output: output.to_owned(),
+        ..Default::default()
+    };
+    while let Some(arg) = args.next() {
+        let value = args.next().map(|v| v.as_str()).unwrap_or_default();
+        match arg.as_str() {
+            "--shift" => edit.shift_ms = Some(value.parse().map_err(|_| usage_error())?),
+            "--fps" => edit.fps = Some(parse_fps(value).ok_or_else(usage_error)?),
+            _ => return Err(usage_error().into()),
+        }
+    }
+    apply(&edit)
+}
+
+pub fn apply(edit: &SubtitleEdit) -> Result<(), Box<dyn Error>> {
+    let input_format = SubtitleFormat::from_path(&edit.input)?;
+    let output_format = SubtitleFormat::from_path(&edit.output)?;
+
+    let content = std::fs::read_to_string(&edit.input)?;
+    let mut doc = SubtitleDocument::parse(&content, &input_format)?;
+    debug!("Parsed {} cues from: {}", doc.cues.len(), edit.input);
+
+    if let Some((from, to)) = edit.fps {
+        doc.rescale(from, to);
+    }
+    if let Some(shift) = edit.shift_ms {
+        doc.shift(shift);
+    }
+
+    std::fs::write(&edit.output, doc.write(&output_format))?;
+    println!("Saved {} cues to: {}", doc.cues.len(), edit.output);
+    Ok(())
+}
+
+fn usage_error() -> TranscoderError {
+    TranscoderError::AbortTranscoding(USAGE.to_owned())
+}
+
+fn parse_fps(value: &str) -> Option<(f64, f64)> {
+    let (from, to) = value.split_once(':')?;
+    match (from.trim().parse::<f64>(), to.trim().parse::<f64>()) {
+        (Ok(from), Ok(to)) if from > 0.0 && to > 0.0 => Some((from, to)),
+        _ => {
+            eprintln!("Invalid frame rate conversion.");
+            None
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::error::SubtitleError;
+use crate::subtitle::{format_time, parse_time, strip_override_tags, Cue, SubtitleDocument};
+
+pub fn parse(content: &str) -> Result<SubtitleDocument, SubtitleError> {
+    let mut doc = SubtitleDocument::default();
+    let mut lines = content.lines().enumerate().peekable();
+
+    while let Some((n, line)) = lines.next() {
+        let line = line.trim();
+        // NOTE and STYLE blocks never contain "-->" so they are skipped together with cue ids
+        if !line.contains("-->") {
+            continue;
+        }
+        let (start, rest) = line
+            .split_once("-->")
+            .ok_or(SubtitleError::InvalidTimestamp(n + 1))?;
+        // the end timestamp may be followed by cue settings, e.g. "align:start"
+        let end = rest.split_whitespace().next().unwrap_or_default();
+        let start = parse_time(start, &['.']).ok_or(SubtitleError::InvalidTimestamp(n + 1))?;
+        let end = parse_time(end, &['.']).ok_or(SubtitleError::InvalidTimestamp(n + 1))?;
+
+        let mut text = Vec::new();
+        while let Some((_, line)) = lines.next_if(|(_, l)| !l.trim().is_empty()) {
+            text.push(line.trim_end());
+        }
+        doc.cues.push(Cue {
+            start,
+            end,
+            text: text.join("\n"),
+            style: None,
+        });
+    }
+    Ok(doc)
+}
+
+pub fn write(doc: &SubtitleDocument) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for cue in doc.cues.iter() {
+        out.push_str(
+            format!(
+                "{} --> {}\n{}\n\n",
+                format_time(cue.start, '.'),
+                format_time(cue.end, '.'),
+                strip_override_tags(&cue.text)
+            )
+            .as_str(),
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_vtt_with_settings_and_notes() {
+        let vtt = "WEBVTT\n\nNOTE a comment\n\nintro\n00:01.000 --> 00:02.000 align:start\nHello\n\n01:00:00.000 --> 01:00:01.250\nBye\n";
+        let doc = parse(vtt).unwrap();
+        assert_eq!(doc.cues.len(), 2);
+        assert_eq!(doc.cues[0].start, 1_000);
+        assert_eq!(doc.cues[0].text, "Hello");
+        assert_eq!(doc.cues[1].end, 3_601_250);
+
+        assert_eq!(
+            write(&doc),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nHello\n\n01:00:00.000 --> 01:00:01.250\nBye\n\n"
+        );
+    }
+}
@@ -4,6 +4,7 @@ use crate::string_utils;
 use log::debug;
 use std::error::Error;
 use std::path::Path;
+use std::str::FromStr;
 
 pub fn read_input() -> String {
     let mut input = String::new();
@@ -13,6 +14,34 @@ pub fn read_input() -> String {
     input.trim().to_owned()
 }
 
+pub fn read_optional<T: FromStr>(name: &str) -> Option<T> {
+    let value = read_input();
+    if value.is_empty() {
+        return None;
+    }
+    match value.parse::<T>() {
+        Ok(v) => Some(v),
+        Err(_) => {
+            eprintln!("Invalid {}.", name);
+            None
+        }
+    }
+}
+
+pub fn read_optional_timestamp(name: &str) -> Option<f64> {
+    let value = read_input();
+    if value.is_empty() {
+        return None;
+    }
+    match string_utils::parse_timestamp(&value) {
+        Ok(v) => Some(v),
+        Err(_) => {
+            eprintln!("Invalid {}.", name);
+            None
+        }
+    }
+}
+
 pub fn ask_input_and_output_file() -> Result<(String, String, String), Box<dyn Error>> {
     let input = ask_input_file()?;
     let format = ask_output_format()?;
@@ -24,7 +53,11 @@ pub fn ask_input_and_output_file() -> Result<(String, String, String), Box<dyn E
 
 #[inline]
 pub fn ask_input_file() -> Result<String, Box<dyn Error>> {
-    println!("Provide video path (e.g. /some/directory/video.mp4):");
+    ask_existing_file("Provide video path (e.g. /some/directory/video.mp4):")
+}
+
+pub fn ask_existing_file(prompt: &str) -> Result<String, Box<dyn Error>> {
+    println!("{}", prompt);
     let path = read_input();
     let file = match Path::new(&path).try_exists() {
         Ok(exists) => match exists {